@@ -2,25 +2,44 @@ use crate::types::*;
 
 const ASG_EWA_LEN: usize = 16;
 const ASG_EWA_SLOW: usize = 8192;
+/// the weight of the coefficient of variation in the adaptive threshold
+const ASG_CV_SCALE: f64 = 1.0;
 
 /// An assignment history used for blocking restart.
 #[derive(Clone, Debug)]
 pub struct ProgressASG {
     ema: Ewa2<ASG_EWA_LEN>,
+    /// the average of squared inputs over the same window as the fast EMA
+    sq: Ewa<ASG_EWA_LEN>,
+    /// derive `threshold` from the variance of inputs (`rst_asg_adaptive`)
+    adaptive: bool,
+    /// the trend under which restarts are blocked, updated by `shift`
+    threshold: f64,
+    /// the factor to scale the fast EMA by at restarts (`rst_relax`)
+    relax: f64,
+    touched: bool,
 }
 
 impl Default for ProgressASG {
     fn default() -> ProgressASG {
         ProgressASG {
             ema: Ewa2::<ASG_EWA_LEN>::new(0.0),
+            sq: Ewa::<ASG_EWA_LEN>::new(0.0),
+            adaptive: false,
+            threshold: 1.0,
+            relax: 1.0,
+            touched: false,
         }
     }
 }
 
 impl Instantiate for ProgressASG {
-    fn instantiate(_config: &Config, _cnf: &CNFDescription) -> Self {
+    fn instantiate(config: &Config, _cnf: &CNFDescription) -> Self {
         ProgressASG {
             ema: Ewa2::new(0.0).with_slow(ASG_EWA_SLOW),
+            adaptive: config.rst_asg_adaptive,
+            relax: config.rst_relax,
+            ..ProgressASG::default()
         }
     }
 }
@@ -30,16 +49,225 @@ impl EmaIF for ProgressASG {
         self.ema.get()
     }
     fn trend(&self) -> f64 {
-        self.ema.trend()
+        // The slow EMA is 0 until a nonzero input.
+        let t = self.ema.trend();
+        if t.is_finite() {
+            t
+        } else {
+            1.0
+        }
+    }
+}
+
+impl ProgressEvaluatorIF for ProgressASG {
+    fn name(&self) -> &'static str {
+        "assign"
+    }
+    fn is_touched(&self) -> bool {
+        self.touched
     }
 }
 
 impl EmaMutIF for ProgressASG {
     type Input = usize;
     fn update(&mut self, n: usize) {
+        self.touched = true;
         self.ema.update(n as f64);
+        if self.adaptive {
+            self.sq.update((n * n) as f64);
+            self.update_threshold();
+        }
     }
     fn as_view(&self) -> &EmaView {
         self.ema.as_view()
     }
 }
+
+impl ProgressASG {
+    /// return the adaptive threshold of trend if `rst_asg_adaptive` is set.
+    pub fn threshold(&self) -> Option<f64> {
+        (self.adaptive && self.touched).then_some(self.threshold)
+    }
+    /// return the coefficient of variation of inputs in the recent window.
+    pub fn coefficient_of_variation(&self) -> f64 {
+        let mean = self.ema.get();
+        if mean <= 0.0 {
+            return 0.0;
+        }
+        (self.sq.get_fast() - mean * mean).max(0.0).sqrt() / mean
+    }
+    /// scale the recent inputs by `rst_relax` at a restart, so that the next
+    /// restart check needs fresh evidence.
+    pub fn shift(&mut self) {
+        if self.relax < 1.0 {
+            self.ema.scale_fast(self.relax);
+            self.sq.scale(self.relax * self.relax);
+        }
+    }
+    /// lower `threshold` as inputs vary widely, since their trend drifts by noise then.
+    fn update_threshold(&mut self) {
+        self.threshold = (1.0 - ASG_CV_SCALE * self.coefficient_of_variation()).max(0.0);
+    }
+}
+
+const LVL_EWA_LEN: usize = 16;
+const LVL_EWA_SLOW: usize = 8192;
+
+/// A decision level history used for restarting on decision level blowups.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressLVL {
+    ema: Ewa2<LVL_EWA_LEN>,
+    threshold: f64,
+    touched: bool,
+}
+
+impl Default for ProgressLVL {
+    fn default() -> ProgressLVL {
+        ProgressLVL {
+            ema: Ewa2::<LVL_EWA_LEN>::new(0.0),
+            threshold: crate::config::RST_LVL_THR,
+            touched: false,
+        }
+    }
+}
+
+impl Instantiate for ProgressLVL {
+    fn instantiate(config: &Config, _cnf: &CNFDescription) -> Self {
+        ProgressLVL {
+            ema: Ewa2::new(0.0).with_slow(LVL_EWA_SLOW),
+            threshold: config.rst_lvl_thr,
+            touched: false,
+        }
+    }
+}
+
+impl EmaIF for ProgressLVL {
+    fn get_fast(&self) -> f64 {
+        self.ema.get()
+    }
+    fn trend(&self) -> f64 {
+        // The slow EMA is 0 until a nonzero input.
+        let t = self.ema.trend();
+        if t.is_finite() {
+            t
+        } else {
+            1.0
+        }
+    }
+}
+
+impl ProgressEvaluatorIF for ProgressLVL {
+    fn name(&self) -> &'static str {
+        "level"
+    }
+    fn is_touched(&self) -> bool {
+        self.touched
+    }
+}
+
+impl EmaMutIF for ProgressLVL {
+    type Input = DecisionLevel;
+    fn update(&mut self, lvl: DecisionLevel) {
+        if !self.touched {
+            // start from the first level, as the slow EMA warms up from 0 too slowly
+            self.touched = true;
+            self.ema = self.ema.clone().with_values(lvl as f64, lvl as f64);
+            return;
+        }
+        self.ema.update(lvl as f64);
+    }
+    fn as_view(&self) -> &EmaView {
+        self.ema.as_view()
+    }
+}
+
+impl ProgressLVL {
+    /// return `true` if decision levels are growing faster than the threshold.
+    pub fn is_active(&self) -> bool {
+        self.threshold < self.trend()
+    }
+    /// return the trend over which `is_active` holds.
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+    /// return the same evaluator with the level history cleared.
+    pub fn clear(&self) -> ProgressLVL {
+        ProgressLVL {
+            ema: Ewa2::new(0.0).with_slow(LVL_EWA_SLOW),
+            threshold: self.threshold,
+            touched: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluator_name() {
+        assert_eq!(ProgressASG::default().name(), "assign");
+        assert_eq!(ProgressLVL::default().name(), "level");
+    }
+
+    #[test]
+    fn test_initial_trend() {
+        let mut asg = ProgressASG::instantiate(&Config::default(), &CNFDescription::default());
+        assert_eq!(asg.trend(), 1.0);
+        asg.update(0);
+        assert_eq!(asg.trend(), 1.0);
+        asg.update(10);
+        assert!(asg.trend().is_finite());
+        let lvl = ProgressLVL::instantiate(&Config::default(), &CNFDescription::default());
+        assert_eq!(lvl.trend(), 1.0);
+        assert!(!lvl.is_active());
+    }
+
+    #[test]
+    fn test_adaptive_threshold() {
+        let config = Config {
+            rst_asg_adaptive: true,
+            ..Config::default()
+        };
+        let mut asg = ProgressASG::instantiate(&config, &CNFDescription::default());
+        assert_eq!(asg.threshold(), None);
+        for _ in 0..100 {
+            asg.update(100);
+        }
+        assert_eq!(asg.threshold(), Some(1.0));
+        for i in 0..100 {
+            asg.update(if i % 2 == 0 { 50 } else { 150 });
+        }
+        let thr = asg.threshold().unwrap();
+        assert!((asg.coefficient_of_variation() - 0.5).abs() < 1e-9);
+        assert!((thr - 0.5).abs() < 1e-9);
+        for _ in 0..100 {
+            asg.update(100);
+        }
+        assert!(thr < asg.threshold().unwrap());
+        let mut asg = ProgressASG::instantiate(&Config::default(), &CNFDescription::default());
+        asg.update(100);
+        assert_eq!(asg.threshold(), None);
+    }
+
+    #[test]
+    fn test_progress_lvl() {
+        let mut lvl = ProgressLVL::instantiate(&Config::default(), &CNFDescription::default());
+        for _ in 0..100_000 {
+            lvl.update(10);
+        }
+        assert!(!lvl.is_active());
+        for l in 10..40 {
+            lvl.update(l);
+        }
+        assert!(lvl.is_active());
+        let lvl = ProgressLVL::instantiate(
+            &Config {
+                rst_lvl_thr: 100.0,
+                ..Config::default()
+            },
+            &CNFDescription::default(),
+        );
+        assert!(!lvl.is_active());
+    }
+}
@@ -20,8 +20,8 @@ mod trail_saving;
 mod var;
 
 pub use self::{
-    propagate::PropagateIF, property::*, select::VarSelectIF, trail_saving::TrailSavingIF,
-    var::VarManipulateIF,
+    ema::ProgressLVL, propagate::PropagateIF, property::*, select::VarSelectIF,
+    trail_saving::TrailSavingIF, var::VarManipulateIF,
 };
 #[cfg(any(feature = "best_phases_tracking", feature = "rephase"))]
 use std::collections::HashMap;
@@ -146,6 +146,14 @@ impl Instantiate for AssignStack {
                 #[cfg(feature = "trail_saving")]
                 self.reason_saved.push(AssignReason::None);
             }
+            SolverEvent::Restart => {
+                self.assign_rate.shift();
+            }
+            SolverEvent::ResetPhase => {
+                for v in self.var.iter_mut().skip(1) {
+                    v.turn_off(FlagVar::PHASE);
+                }
+            }
             SolverEvent::Reinitialize => {
                 self.cancel_until(self.root_level);
                 debug_assert_eq!(self.decision_level(), self.root_level);
@@ -315,6 +323,13 @@ impl AssignIF for AssignStack {
     }
 }
 
+impl AssignStack {
+    /// return the assign rate evaluator used for blocking restart.
+    pub fn assign_progress(&self) -> &ProgressASG {
+        &self.assign_rate
+    }
+}
+
 impl fmt::Display for AssignStack {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let v = self.trail.iter().map(|l| i32::from(*l)).collect::<Vec<_>>();
@@ -377,6 +377,14 @@ fn report(s: &Solver, out: &mut dyn Write) -> std::io::Result<()> {
             .as_bytes(),
         )?;
     }
+    if s.state.config.io_lbd_hist {
+        out.write_all(b"c \n")?;
+        let hist = s.cdb.lbd_histogram();
+        for (lbd, num) in hist.iter().enumerate().filter(|(_, n)| 0 < **n) {
+            let plus = if lbd + 1 == hist.len() { "+" } else { " " };
+            out.write_all(format!("c   lbd::{:>3}{}{:>31}\n", lbd, plus, num).as_bytes())?;
+        }
+    }
 
     out.write_all(b"c \n")?;
     Ok(())
@@ -196,8 +196,10 @@ impl Instantiate for ClauseDB {
                 self.lbd_temp.push(0);
             }
             SolverEvent::Restart => {
-                // self.lbd.reset_to(self.lb_entanglement.get());
-                // self.lbd.reset_to(0.0);
+                self.lbd.shift();
+            }
+            SolverEvent::Reinitialize => {
+                self.lbd.reset();
             }
             _ => (),
         }
@@ -1027,6 +1029,25 @@ impl ClauseDBIF for ClauseDB {
             }
         }
     }
+    fn reduce_to(&mut self, asg: &mut impl AssignIF, limit: usize) {
+        if self.num_learnt <= limit {
+            return;
+        }
+        let mut perm: Vec<OrderedProxy<usize>> = Vec::with_capacity(self.num_learnt);
+        for (i, c) in self
+            .clause
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, c)| !c.is_dead() && c.is(FlagClause::LEARNT))
+        {
+            perm.push(OrderedProxy::new(i, c.pure_weight(asg)));
+        }
+        perm.sort();
+        for i in &perm[limit.min(perm.len())..] {
+            self.remove_clause(ClauseId::from(i.to()));
+        }
+    }
     fn reset(&mut self) {
         debug_assert!(1 < self.clause.len());
         for (i, c) in &mut self.clause.iter_mut().enumerate().skip(1) {
@@ -1191,6 +1212,14 @@ impl ClauseDBIF for ClauseDB {
 }
 
 impl ClauseDB {
+    /// return the histogram of learnt clauses' LBDs. See `ProgressLBD::lbd_histogram`.
+    pub fn lbd_histogram(&self) -> &[usize] {
+        self.lbd.lbd_histogram()
+    }
+    /// return the LBD evaluator used for forcing restart.
+    pub fn lbd_progress(&self) -> &ProgressLBD {
+        &self.lbd
+    }
     /// formula: -a => b and b => c implies -a => c
     /// clause: [a, b] and [-b, c] deduces [a, c]
     /// map: [a].get(b), [!b].get(c), [a].get(c)
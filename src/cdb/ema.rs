@@ -2,6 +2,8 @@ use crate::types::*;
 
 const LBD_EWA_LEN: usize = 16;
 const LBD_EWA_SLOW: usize = 8192;
+/// The number of histogram buckets; the last one collects all larger LBDs.
+pub const LBD_HISTOGRAM_SIZE: usize = 32;
 
 /// An EMA of learnt clauses' LBD, used for forcing restart.
 #[derive(Clone, Debug)]
@@ -9,6 +11,14 @@ pub struct ProgressLBD {
     ema: Ewa2<LBD_EWA_LEN>,
     num: usize,
     sum: usize,
+    hist: Vec<usize>,
+    /// the upper bound of LBDs fed to the EMA, or 0 for no bound
+    cap: u16,
+    /// blend the ratio to the average LBD into `trend`
+    use_average: bool,
+    /// the factor to scale the fast EMA by at restarts (`rst_relax`)
+    relax: f64,
+    touched: bool,
 }
 
 impl Default for ProgressLBD {
@@ -17,14 +27,22 @@ impl Default for ProgressLBD {
             ema: Ewa2::new(0.0),
             num: 0,
             sum: 0,
+            hist: vec![0; LBD_HISTOGRAM_SIZE],
+            cap: 0,
+            use_average: crate::config::RST_LBD_USE_AVERAGE,
+            relax: 1.0,
+            touched: false,
         }
     }
 }
 
 impl Instantiate for ProgressLBD {
-    fn instantiate(_config: &Config, _: &CNFDescription) -> Self {
+    fn instantiate(config: &Config, _: &CNFDescription) -> Self {
         ProgressLBD {
             ema: Ewa2::new(0.0).with_slow(LBD_EWA_SLOW),
+            cap: config.rst_lbd_cap,
+            use_average: config.rst_lbd_use_average,
+            relax: config.rst_relax,
             ..ProgressLBD::default()
         }
     }
@@ -38,15 +56,37 @@ impl EmaIF for ProgressLBD {
         self.ema.get_fast()
     }
     fn trend(&self) -> f64 {
-        self.ema.trend()
+        // The slow EMA is 0 until a nonzero input.
+        let mut t = self.ema.trend();
+        if self.use_average && 0 < self.sum {
+            t = t.max(self.ema.get_fast() * self.num as f64 / self.sum as f64);
+        }
+        if t.is_finite() {
+            t
+        } else {
+            1.0
+        }
+    }
+}
+
+impl ProgressEvaluatorIF for ProgressLBD {
+    fn name(&self) -> &'static str {
+        "LBD"
+    }
+    fn is_touched(&self) -> bool {
+        self.touched
     }
 }
 
 impl EmaMutIF for ProgressLBD {
     type Input = u16;
     fn update(&mut self, d: Self::Input) {
+        self.touched = true;
         self.num += 1;
         self.sum += d as usize;
+        self.hist[(d as usize).min(LBD_HISTOGRAM_SIZE - 1)] += 1;
+        // A huge LBD would skew the slow EMA for thousands of conflicts.
+        let d = if 0 < self.cap { d.min(self.cap) } else { d };
         self.ema.update(d as f64);
     }
     fn reset_to(&mut self, val: f64) {
@@ -56,3 +96,131 @@ impl EmaMutIF for ProgressLBD {
         self.ema.as_view()
     }
 }
+
+impl ProgressLBD {
+    /// return the number of learnt clauses for each LBD so far.
+    /// Index `i` counts LBD `i`, and the last bucket counts all LBDs
+    /// equal to or larger than `LBD_HISTOGRAM_SIZE - 1`.
+    pub fn lbd_histogram(&self) -> &[usize] {
+        &self.hist
+    }
+    /// clear the LBD history, keeping the parameters, e.g. between incremental solves.
+    pub fn reset(&mut self) {
+        self.ema.reset();
+        self.num = 0;
+        self.sum = 0;
+        self.hist.iter_mut().for_each(|n| *n = 0);
+        self.touched = false;
+    }
+    /// scale the recent LBDs by `rst_relax` and restart the average at a restart,
+    /// so that the next restart check needs fresh evidence.
+    pub fn shift(&mut self) {
+        if self.relax < 1.0 {
+            self.ema.scale_fast(self.relax);
+            self.num = 0;
+            self.sum = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initial_trend() {
+        let mut lbd = ProgressLBD::instantiate(&Config::default(), &CNFDescription::default());
+        assert_eq!(lbd.trend(), 1.0);
+        lbd.update(0);
+        assert_eq!(lbd.trend(), 1.0);
+        lbd.update(4);
+        assert!(lbd.trend().is_finite());
+    }
+
+    #[test]
+    fn test_lbd_use_average() {
+        let trends = |use_average: bool| {
+            let config = Config {
+                rst_lbd_use_average: use_average,
+                ..Config::default()
+            };
+            let mut lbd = ProgressLBD::instantiate(&config, &CNFDescription::default());
+            let mut trends = Vec::new();
+            // LBDs rise for good after a long low phase
+            for d in [2, 8] {
+                for _ in 0..20_000 {
+                    lbd.update(d);
+                }
+                trends.push(lbd.trend());
+            }
+            trends
+        };
+        let (with, without) = (trends(true), trends(false));
+        assert_eq!(with[0], without[0]);
+        // The slow EMA has caught up with the rise, but the overall average has not.
+        assert!(without[1] < 1.2);
+        assert!(1.5 < with[1]);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut lbd = ProgressLBD::instantiate(&Config::default(), &CNFDescription::default());
+        for d in [2, 8, 3, 40, 5] {
+            lbd.update(d);
+        }
+        lbd.reset();
+        assert!(!lbd.is_touched());
+        assert_eq!(lbd.get_fast(), 0.0);
+        assert_eq!(lbd.trend(), 1.0);
+        assert!(lbd.lbd_histogram().iter().all(|n| *n == 0));
+        let mut fresh = ProgressLBD::instantiate(&Config::default(), &CNFDescription::default());
+        lbd.update(4);
+        fresh.update(4);
+        assert_eq!(lbd.as_view(), fresh.as_view());
+        assert_eq!(lbd.trend(), fresh.trend());
+    }
+
+    #[test]
+    fn test_lbd_histogram() {
+        let mut lbd = ProgressLBD::instantiate(&Config::default(), &CNFDescription::default());
+        for d in [2, 2, 3, 5, 2, 40, 31, 30, 1000] {
+            lbd.update(d);
+        }
+        let hist = lbd.lbd_histogram();
+        assert_eq!(hist.len(), LBD_HISTOGRAM_SIZE);
+        assert_eq!(hist[2], 3);
+        assert_eq!(hist[3], 1);
+        assert_eq!(hist[4], 0);
+        assert_eq!(hist[5], 1);
+        assert_eq!(hist[30], 1);
+        assert_eq!(hist[LBD_HISTOGRAM_SIZE - 1], 3);
+        assert_eq!(hist.iter().sum::<usize>(), 9);
+    }
+
+    #[test]
+    fn test_evaluator_name() {
+        assert_eq!(ProgressLBD::default().name(), "LBD");
+    }
+
+    #[test]
+    fn test_lbd_cap() {
+        let trend_after = |cap: u16, spike: u16| {
+            let config = Config {
+                rst_lbd_cap: cap,
+                ..Config::default()
+            };
+            let mut lbd = ProgressLBD::instantiate(&config, &CNFDescription::default());
+            for _ in 0..1_000 {
+                lbd.update(4);
+            }
+            lbd.update(spike);
+            for _ in 0..100 {
+                lbd.update(4);
+            }
+            lbd.trend()
+        };
+        let normal = trend_after(0, 4);
+        assert!(trend_after(0, 5_000) < 0.5 * normal);
+        assert!((trend_after(16, 5_000) / normal - 1.0).abs() < 0.05);
+    }
+}
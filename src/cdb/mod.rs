@@ -20,12 +20,12 @@ mod watch_cache;
 pub use self::{
     binary::{BinaryLinkDB, BinaryLinkList},
     cid::ClauseIdIF,
+    ema::ProgressLBD,
     property::*,
     unsat_certificate::CertificationStore,
     vivify::VivifyIF,
 };
 use {
-    self::ema::ProgressLBD,
     crate::{assign::AssignIF, types::*},
     std::{
         num::NonZeroU32,
@@ -132,6 +132,11 @@ pub trait ClauseDBIF:
     /// # CAVEAT
     /// *precondition*: decision level == 0.
     fn reduce(&mut self, asg: &mut impl AssignIF, portion: usize);
+    /// remove the worst learnt clauses, vivification targets included,
+    /// until at most `limit` ones remain.
+    /// # CAVEAT
+    /// *precondition*: decision level == 0.
+    fn reduce_to(&mut self, asg: &mut impl AssignIF, limit: usize);
     /// remove all learnt clauses.
     fn reset(&mut self);
     /// update flags.
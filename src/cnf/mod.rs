@@ -213,6 +213,17 @@ impl CnfIf for CNF {
     }
 }
 
+impl CNF {
+    /// return all the clauses including unit ones.
+    pub fn to_vec(&self) -> Vec<Clause> {
+        self.assign
+            .iter()
+            .map(|l| vec![*l])
+            .chain(self.clauses.iter().cloned())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,6 +245,8 @@ mod tests {
         assert_eq!(line.next(), Some("p cnf 4 2"));
         assert_eq!(line.next(), Some("1 3 2 0"));
         assert_eq!(line.next(), Some("-1 -4 3 0"));
+        let cnf = CNF::from_vec_i32(vec![vec![-2], vec![1, 2]]).unwrap();
+        assert_eq!(cnf.to_vec(), vec![vec![-2], vec![1, 2]]);
     }
     #[test]
     fn test_load_uf8() {
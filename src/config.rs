@@ -3,6 +3,284 @@ use {crate::types::DecisionLevel, std::path::PathBuf};
 
 pub const CERTIFICATION_DEFAULT_FILENAME: &str = "proof.drat";
 
+pub const RST_LBD_SEEN_THR: u16 = 0;
+pub const RST_DB_SIZE_THR: f64 = 0.0;
+pub const RST_NEAR_SOL_THR: f64 = 0.0;
+pub const RST_ON_RESCALE: bool = false;
+pub const RST_JITTER: usize = 0;
+pub const RST_REUSE_THR: f64 = 0.0;
+pub const RST_MODE_SWITCH: bool = false;
+pub const RST_STB_BUDGET: usize = 0;
+pub const RST_BANDIT: bool = false;
+pub const RST_LOCALITY_THR: f64 = 0.0;
+pub const RST_STRICT: bool = true;
+pub const RST_EPOCH: usize = 0;
+pub const RST_LBD_CAP: u16 = 0;
+pub const RST_STEP: usize = 0;
+pub const RST_PARTIAL: bool = false;
+pub const RST_STB_TRANSITION_RESTART: bool = false;
+pub const RST_HARDNESS: bool = false;
+pub const RST_LVL_THR: f64 = 1.4;
+pub const RST_FIXED_STAGE: bool = false;
+pub const RST_LUBY_INC: f64 = 2.0;
+pub const RST_NEVER: bool = false;
+pub const RST_LOCAL_SEARCH: usize = 0;
+pub const RST_EAGERNESS: f64 = 1.0;
+pub const RST_VOTE_THR: f64 = 0.0;
+pub const RST_VOTE_LBD: f64 = 1.0;
+pub const RST_VOTE_ENT: f64 = 1.0;
+pub const RST_VOTE_REUSE: f64 = 1.0;
+pub const RST_STB_GRACE: usize = 0;
+pub const RST_LBD_USE_AVERAGE: bool = true;
+pub const RST_RCC_THR: f64 = 0.0;
+pub const RST_MIN_INTERVAL: usize = 0;
+pub const RST_GEOMETRIC: bool = false;
+pub const RST_GEOMETRIC_INC: f64 = 1.5;
+pub const RST_GEOMETRIC_STEP: usize = 100;
+pub const RST_RESET_PHASE: bool = false;
+pub const RST_ASG_ADAPTIVE: bool = false;
+pub const RST_MAX_COUNT: usize = 0;
+pub const RST_RELAX: f64 = 1.0;
+pub const RST_HYBRID: bool = false;
+pub const RST_BKT_PWR: f64 = 0.0;
+pub const RST_BKT_THR: f64 = 1000.0;
+pub const RST_BKT_DECAY: usize = 0;
+pub const RST_STB_MAX: usize = 0;
+pub const RST_LVL_FORCE: bool = false;
+
+/// Name, default value and description of each restart option.
+/// `bool` values are represented by 0.0 and 1.0.
+pub const RESTART_OPTIONS: [(&str, f64, &str); 44] = [
+    (
+        "rst_lbd_seen_thr",
+        RST_LBD_SEEN_THR as f64,
+        "LBD a learnt must exceed before the first restart (0: disabled)",
+    ),
+    (
+        "rst_db_size_thr",
+        RST_DB_SIZE_THR,
+        "Learnt/var ratio to suppress restarts (0.0: disabled)",
+    ),
+    (
+        "rst_near_sol_thr",
+        RST_NEAR_SOL_THR,
+        "Unassigned-var trend to suppress restarts (0.0: disabled; needs 'assign_rate')",
+    ),
+    (
+        "rst_on_rescale",
+        RST_ON_RESCALE as u8 as f64,
+        "Force a restart after rescaling var activities",
+    ),
+    (
+        "rst_jitter",
+        RST_JITTER as f64,
+        "Max #conflicts to postpone a restart, derived from the seed (0: disabled)",
+    ),
+    (
+        "rst_reuse_thr",
+        RST_REUSE_THR,
+        "Rate of conflicts by recent learnts to force a restart (0.0: disabled)",
+    ),
+    (
+        "rst_mode_switch",
+        RST_MODE_SWITCH as u8 as f64,
+        "Alternate dynamic and Luby restarts at each stage",
+    ),
+    (
+        "rst_stb_budget",
+        RST_STB_BUDGET as f64,
+        "Max #restarts in a stable phase (0: unlimited)",
+    ),
+    (
+        "rst_bandit",
+        RST_BANDIT as u8 as f64,
+        "Select the restart mode with the lowest LBD at each stage",
+    ),
+    (
+        "rst_locality_thr",
+        RST_LOCALITY_THR,
+        "Var overlap of learnts to block restarts (0.0: disabled)",
+    ),
+    (
+        "rst_strict",
+        RST_STRICT as u8 as f64,
+        "Use '<' instead of '<=' in comparisons with restart thresholds",
+    ),
+    (
+        "rst_epoch",
+        RST_EPOCH as f64,
+        "Align restarts to multiples of this #conflicts (0: disabled)",
+    ),
+    (
+        "rst_lbd_cap",
+        RST_LBD_CAP as f64,
+        "Upper bound of LBDs fed to the LBD EMA (0: disabled)",
+    ),
+    (
+        "rst_step",
+        RST_STEP as f64,
+        "Min #conflicts between restarts in any mode (0: disabled, same as 1 in search)",
+    ),
+    (
+        "rst_partial",
+        RST_PARTIAL as u8 as f64,
+        "Backjump halfway instead of blocking a due restart by locality or assign trend",
+    ),
+    (
+        "rst_stb_transition_restart",
+        RST_STB_TRANSITION_RESTART as u8 as f64,
+        "Force a restart after switching between dynamic and Luby restarts",
+    ),
+    (
+        "rst_hardness",
+        RST_HARDNESS as u8 as f64,
+        "Scale restart energy by the clause/var ratio of the problem",
+    ),
+    (
+        "rst_lvl_thr",
+        RST_LVL_THR,
+        "Decision level trend threshold of ProgressLVL",
+    ),
+    (
+        "rst_fixed_stage",
+        RST_FIXED_STAGE as u8 as f64,
+        "Keep the stage span fixed instead of growing it by cycle",
+    ),
+    (
+        "rst_luby_inc",
+        RST_LUBY_INC,
+        "Base of the Luby series in stable mode",
+    ),
+    (
+        "rst_never",
+        RST_NEVER as u8 as f64,
+        "Disable restarts by heuristics, except at the end of stages",
+    ),
+    (
+        "rst_local_search",
+        RST_LOCAL_SEARCH as f64,
+        "#flips of local search to improve phases at restarts (0: disabled)",
+    ),
+    (
+        "rst_eagerness",
+        RST_EAGERNESS,
+        "Scale of all restart thresholds toward more restarts (1.0: as configured)",
+    ),
+    (
+        "rst_vote_thr",
+        RST_VOTE_THR,
+        "Weighted sum of votes to force a restart, instead of penetration energy (0.0: disabled)",
+    ),
+    (
+        "rst_vote_lbd",
+        RST_VOTE_LBD,
+        "Vote weight of a rising LBD trend",
+    ),
+    (
+        "rst_vote_ent",
+        RST_VOTE_ENT,
+        "Vote weight of a rising entanglement trend",
+    ),
+    (
+        "rst_vote_reuse",
+        RST_VOTE_REUSE,
+        "Vote weight of a reuse rate over rst_reuse_thr",
+    ),
+    (
+        "rst_stb_grace",
+        RST_STB_GRACE as f64,
+        "#conflicts of a pure dynamic phase before mode switching starts (0: none)",
+    ),
+    (
+        "rst_lbd_use_average",
+        RST_LBD_USE_AVERAGE as u8 as f64,
+        "Let the LBD trend be at least the ratio of the recent LBD to the average",
+    ),
+    (
+        "rst_rcc_thr",
+        RST_RCC_THR,
+        "Trend of recurring conflict complexity to force a restart (0.0: disabled)",
+    ),
+    (
+        "rst_min_interval",
+        RST_MIN_INTERVAL as f64,
+        "Hard min #conflicts between restarts, including partial and epoch-aligned ones (0: none)",
+    ),
+    (
+        "rst_geometric",
+        RST_GEOMETRIC as u8 as f64,
+        "Restart by a geometric series of intervals as MiniSat does",
+    ),
+    (
+        "rst_geometric_inc",
+        RST_GEOMETRIC_INC,
+        "Growth rate of geometric restart intervals",
+    ),
+    (
+        "rst_geometric_step",
+        RST_GEOMETRIC_STEP as f64,
+        "#conflicts to the first restart in geometric mode",
+    ),
+    (
+        "rst_reset_phase",
+        RST_RESET_PHASE as u8 as f64,
+        "Reset saved phases at each restart",
+    ),
+    (
+        "rst_asg_adaptive",
+        RST_ASG_ADAPTIVE as u8 as f64,
+        "Derive the unassigned-var trend threshold from its variance (needs 'assign_rate')",
+    ),
+    (
+        "rst_max_count",
+        RST_MAX_COUNT as f64,
+        "Max #restarts per solve, after which restarts stop (0: unlimited)",
+    ),
+    (
+        "rst_relax",
+        RST_RELAX,
+        "Factor to scale the assign and LBD EMAs by at restarts (1.0: no relaxation)",
+    ),
+    (
+        "rst_hybrid",
+        RST_HYBRID as u8 as f64,
+        "Restart by Luby series, or by LBD after half of a Luby interval",
+    ),
+    (
+        "rst_bkt_pwr",
+        RST_BKT_PWR,
+        "Power of LBDs filling the bucket to force restarts (0.0: disabled)",
+    ),
+    (
+        "rst_bkt_thr",
+        RST_BKT_THR,
+        "Sum of powered LBDs which fills the bucket",
+    ),
+    (
+        "rst_bkt_decay",
+        RST_BKT_DECAY as f64,
+        "Curve to move the bucket power to 1.0 at restarts (0: none, 1: linear, 2: inverse, 3: exponential)",
+    ),
+    (
+        "rst_stb_max",
+        RST_STB_MAX as f64,
+        "Max #learnts of a stage, at which stage spans saturate (0: unlimited)",
+    ),
+    (
+        "rst_lvl_force",
+        RST_LVL_FORCE as u8 as f64,
+        "Force a restart when the decision level trend exceeds rst_lvl_thr",
+    ),
+];
+
+/// return the default value and description of a restart option.
+pub fn restart_option(name: &str) -> Option<(f64, &'static str)> {
+    RESTART_OPTIONS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, val, desc)| (*val, *desc))
+}
+
 /// Configuration built from command line options
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -15,9 +293,18 @@ pub struct Config {
     /// Soft limit of #clauses (6MC/GB)
     pub c_cls_lim: usize,
 
+    /// Cap of #learnt clauses, forcing a reduction when exceeded
+    pub c_max_learnt: Option<usize>,
+
+    /// Prior belief on satisfiability: fewer restarts if SAT, more if UNSAT
+    pub c_expect_sat: Option<bool>,
+
     /// CPU time limit in sec.
     pub c_timeout: f64,
 
+    /// Seed for randomized heuristics
+    pub c_seed: u64,
+
     //
     //## I/O configuration
     //
@@ -36,6 +323,15 @@ pub struct Config {
     /// Result filename/stdout
     pub io_rfile: PathBuf,
 
+    /// Restart audit log filename (empty: disabled)
+    pub io_afile: PathBuf,
+
+    /// Interval of 'c' stat lines in #conflicts (0: disabled)
+    pub io_stat_int: usize,
+
+    /// Print the histogram of learnt clauses' LBDs at the end
+    pub io_lbd_hist: bool,
+
     /// Disable coloring
     pub no_color: bool,
 
@@ -73,6 +369,143 @@ pub struct Config {
     //## vivifier
     //
 
+    //
+    //## restart
+    //
+    /// LBD a learnt must exceed before the first restart (0: disabled)
+    pub rst_lbd_seen_thr: u16,
+
+    /// Learnt/var ratio to suppress restarts (0.0: disabled)
+    pub rst_db_size_thr: f64,
+
+    /// Unassigned-var trend to suppress restarts (0.0: disabled; needs 'assign_rate')
+    pub rst_near_sol_thr: f64,
+
+    /// Force a restart after rescaling var activities
+    pub rst_on_rescale: bool,
+
+    /// Max #conflicts to postpone a restart, derived from the seed (0: disabled)
+    pub rst_jitter: usize,
+
+    /// Rate of conflicts by recent learnts to force a restart (0.0: disabled)
+    pub rst_reuse_thr: f64,
+
+    /// Alternate dynamic and Luby restarts at each stage
+    pub rst_mode_switch: bool,
+
+    /// Max #restarts in a stable phase (0: unlimited)
+    pub rst_stb_budget: usize,
+
+    /// Select the restart mode with the lowest LBD at each stage
+    pub rst_bandit: bool,
+
+    /// Var overlap of learnts to block restarts (0.0: disabled)
+    pub rst_locality_thr: f64,
+
+    /// Use '<' instead of '<=' in comparisons with restart thresholds
+    pub rst_strict: bool,
+
+    /// Align restarts to multiples of this #conflicts (0: disabled)
+    pub rst_epoch: usize,
+
+    /// Upper bound of LBDs fed to the LBD EMA (0: disabled)
+    pub rst_lbd_cap: u16,
+
+    /// Min #conflicts between restarts in any mode (0: disabled, same as 1 in search)
+    pub rst_step: usize,
+
+    /// Backjump halfway instead of blocking a due restart by locality or assign trend
+    pub rst_partial: bool,
+
+    /// Force a restart after switching between dynamic and Luby restarts
+    pub rst_stb_transition_restart: bool,
+
+    /// Scale restart energy by the clause/var ratio of the problem
+    pub rst_hardness: bool,
+
+    /// Decision level trend threshold of ProgressLVL
+    pub rst_lvl_thr: f64,
+
+    /// Keep the stage span fixed instead of growing it by cycle
+    pub rst_fixed_stage: bool,
+
+    /// Base of the Luby series in stable mode
+    pub rst_luby_inc: f64,
+
+    /// Disable restarts by heuristics, except at the end of stages
+    pub rst_never: bool,
+
+    /// #flips of local search to improve phases at restarts (0: disabled).
+    /// Each run evaluates all irredundant clauses once; their lists are rebuilt
+    /// only after a root level assignment or a simplification.
+    pub rst_local_search: usize,
+
+    /// Scale of all restart thresholds toward more restarts (1.0: as configured)
+    pub rst_eagerness: f64,
+
+    /// Weighted sum of votes to force a restart, instead of penetration energy (0.0: disabled)
+    pub rst_vote_thr: f64,
+
+    /// Vote weight of a rising LBD trend
+    pub rst_vote_lbd: f64,
+
+    /// Vote weight of a rising entanglement trend
+    pub rst_vote_ent: f64,
+
+    /// Vote weight of a reuse rate over rst_reuse_thr
+    pub rst_vote_reuse: f64,
+
+    /// #conflicts of a pure dynamic phase before mode switching starts (0: none)
+    pub rst_stb_grace: usize,
+
+    /// Let the LBD trend be at least the ratio of the recent LBD to the average
+    pub rst_lbd_use_average: bool,
+
+    /// Trend of recurring conflict complexity to force a restart (0.0: disabled)
+    pub rst_rcc_thr: f64,
+
+    /// Hard min #conflicts between restarts, including partial and epoch-aligned ones (0: none)
+    pub rst_min_interval: usize,
+
+    /// Restart by a geometric series of intervals as MiniSat does
+    pub rst_geometric: bool,
+
+    /// Growth rate of geometric restart intervals
+    pub rst_geometric_inc: f64,
+
+    /// #conflicts to the first restart in geometric mode
+    pub rst_geometric_step: usize,
+
+    /// Reset saved phases at each restart
+    pub rst_reset_phase: bool,
+
+    /// Derive the unassigned-var trend threshold from its variance (needs 'assign_rate')
+    pub rst_asg_adaptive: bool,
+
+    /// Max #restarts per solve, after which restarts stop (0: unlimited)
+    pub rst_max_count: usize,
+
+    /// Factor to scale the assign and LBD EMAs by at restarts (1.0: no relaxation)
+    pub rst_relax: f64,
+
+    /// Restart by Luby series, or by LBD after half of a Luby interval
+    pub rst_hybrid: bool,
+
+    /// Power of LBDs filling the bucket to force restarts (0.0: disabled)
+    pub rst_bkt_pwr: f64,
+
+    /// Sum of powered LBDs which fills the bucket
+    pub rst_bkt_thr: f64,
+
+    /// Curve to move the bucket power to 1.0 at restarts (0: none, 1: linear, 2: inverse, 3: exponential)
+    pub rst_bkt_decay: usize,
+
+    /// Max #learnts of a stage, at which stage spans saturate (0: unlimited)
+    pub rst_stb_max: usize,
+
+    /// Force a restart when the decision level trend exceeds rst_lvl_thr
+    pub rst_lvl_force: bool,
+
     //
     //## var rewarding
     //
@@ -87,13 +520,19 @@ impl Default for Config {
         Config {
             c_cbt_thr: 100,
             c_cls_lim: 0,
+            c_max_learnt: None,
+            c_expect_sat: None,
             c_timeout: 5000.0,
+            c_seed: 0,
 
             splr_interface: false,
             cnf_file: PathBuf::new(),
             io_odir: PathBuf::from("."),
             io_pfile: PathBuf::from(CERTIFICATION_DEFAULT_FILENAME),
             io_rfile: PathBuf::new(),
+            io_afile: PathBuf::new(),
+            io_stat_int: 0,
+            io_lbd_hist: false,
             no_color: false,
             quiet_mode: false,
             show_journal: false,
@@ -107,6 +546,51 @@ impl Default for Config {
             elm_grw_lim: 0,
             elm_var_occ: 20000,
 
+            rst_lbd_seen_thr: RST_LBD_SEEN_THR,
+            rst_db_size_thr: RST_DB_SIZE_THR,
+            rst_near_sol_thr: RST_NEAR_SOL_THR,
+            rst_on_rescale: RST_ON_RESCALE,
+            rst_jitter: RST_JITTER,
+            rst_reuse_thr: RST_REUSE_THR,
+            rst_mode_switch: RST_MODE_SWITCH,
+            rst_stb_budget: RST_STB_BUDGET,
+            rst_bandit: RST_BANDIT,
+            rst_locality_thr: RST_LOCALITY_THR,
+            rst_strict: RST_STRICT,
+            rst_epoch: RST_EPOCH,
+            rst_lbd_cap: RST_LBD_CAP,
+            rst_step: RST_STEP,
+            rst_partial: RST_PARTIAL,
+            rst_stb_transition_restart: RST_STB_TRANSITION_RESTART,
+            rst_hardness: RST_HARDNESS,
+            rst_lvl_thr: RST_LVL_THR,
+            rst_fixed_stage: RST_FIXED_STAGE,
+            rst_luby_inc: RST_LUBY_INC,
+            rst_never: RST_NEVER,
+            rst_local_search: RST_LOCAL_SEARCH,
+            rst_eagerness: RST_EAGERNESS,
+            rst_vote_thr: RST_VOTE_THR,
+            rst_vote_lbd: RST_VOTE_LBD,
+            rst_vote_ent: RST_VOTE_ENT,
+            rst_vote_reuse: RST_VOTE_REUSE,
+            rst_stb_grace: RST_STB_GRACE,
+            rst_lbd_use_average: RST_LBD_USE_AVERAGE,
+            rst_rcc_thr: RST_RCC_THR,
+            rst_min_interval: RST_MIN_INTERVAL,
+            rst_geometric: RST_GEOMETRIC,
+            rst_geometric_inc: RST_GEOMETRIC_INC,
+            rst_geometric_step: RST_GEOMETRIC_STEP,
+            rst_reset_phase: RST_RESET_PHASE,
+            rst_asg_adaptive: RST_ASG_ADAPTIVE,
+            rst_max_count: RST_MAX_COUNT,
+            rst_relax: RST_RELAX,
+            rst_hybrid: RST_HYBRID,
+            rst_bkt_pwr: RST_BKT_PWR,
+            rst_bkt_thr: RST_BKT_THR,
+            rst_bkt_decay: RST_BKT_DECAY,
+            rst_stb_max: RST_STB_MAX,
+            rst_lvl_force: RST_LVL_FORCE,
+
             #[cfg(feature = "EVSIDS")]
             vrw_dcy_rat: 0.98,
             #[cfg(feature = "LRB_rewarding")]
@@ -134,11 +618,11 @@ impl Config {
         while let Some(arg) = iter.next() {
             if let Some(stripped) = arg.strip_prefix("--") {
                 let flags = [
-                    "no-color", "quiet", "certify", "journal", "log", "help", "version",
+                    "no-color", "quiet", "certify", "journal", "log", "lbd-hist", "help", "version",
                 ];
                 let options_usize = ["cl", "stat", "ecl", "evl", "evo"];
                 let options_f64 = ["timeout", "cdr", "vdr", "vds"];
-                let options_path = ["dir", "proof", "result"];
+                let options_path = ["dir", "proof", "result", "audit"];
                 let seg: Vec<&str> = stripped.split('=').collect();
                 match seg.len() {
                     1 => {
@@ -150,6 +634,7 @@ impl Config {
                                 "certify" => self.use_certification = true,
                                 "journal" => self.show_journal = true,
                                 "log" => self.use_log = true,
+                                "lbd-hist" => self.io_lbd_hist = true,
                                 "help" => help = true,
                                 "version" => version = true,
                                 _ => panic!("invalid flag: {}", name),
@@ -162,6 +647,7 @@ impl Config {
                                         "ecl" => self.elm_cls_lim = val,
                                         "evl" => self.elm_grw_lim = val,
                                         "evo" => self.elm_var_occ = val,
+                                        "stat" => self.io_stat_int = val,
                                         _ => panic!("invalid option: {}", name),
                                     }
                                 } else {
@@ -193,6 +679,7 @@ impl Config {
                                     "dir" => self.io_odir = PathBuf::from(val),
                                     "proof" => self.io_pfile = PathBuf::from(val),
                                     "result" => self.io_rfile = PathBuf::from(val),
+                                    "audit" => self.io_afile = PathBuf::from(val),
                                     _ => panic!("invalid option: {}", name),
                                 }
                             } else {
@@ -321,6 +808,7 @@ FLAGS:
   -c, --certify             Writes a DRAT UNSAT certification file
   -j, --journal             Shows log about restart stages
   -l, --log                 Uses Glucose-like progress report
+      --lbd-hist            Prints the histogram of learnt clauses' LBDs
   -V, --version             Prints version information
 OPTIONS:
 {}      --cl <c-cls-lim>      Soft limit of #clauses (6MC/GB){:>10}
@@ -331,6 +819,8 @@ OPTIONS:
   -o, --dir <io-outdir>     Output directory                {:>10}
   -p, --proof <io-pfile>    DRAT Cert. filename                 {:>10}
   -r, --result <io-rfile>   Result filename/stdout             {:>10}
+      --audit <io-afile>    Restart audit log filename         {:>10}
+      --stat <io-stat-int>  Interval of 'c' stat lines     {:>10}
       --vdr <vrw-dcy-rat>   Var reward decay rate             {:>10.2}
 {}ARGS:
   <cnf-file>    DIMACS CNF file
@@ -348,6 +838,8 @@ OPTIONS:
         config.io_odir.to_string_lossy(),
         config.io_pfile.to_string_lossy(),
         config.io_rfile.to_string_lossy(),
+        config.io_afile.to_string_lossy(),
+        config.io_stat_int,
         config.vrw_dcy_rat,
         OPTION!(
             "EVSIDS",
@@ -369,6 +861,121 @@ where
     }
 }
 
+/// Errors on invalid or contradictory configuration.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    /// the option (first) has no effect without the feature (second).
+    FeatureRequired(&'static str, &'static str),
+    /// the options (first and second) select contradictory restart modes.
+    Conflict(&'static str, &'static str),
+    /// the option has a value out of its range.
+    OutOfRange(&'static str),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::FeatureRequired(opt, feature) => {
+                write!(f, "{} requires feature '{}'", opt, feature)
+            }
+            ConfigError::Conflict(opt1, opt2) => {
+                write!(f, "{} can't be used with {}", opt1, opt2)
+            }
+            ConfigError::OutOfRange(opt) => write!(f, "{} is out of range", opt),
+        }
+    }
+}
+
+/// A builder of [`Config`] to set restart options by chained calls, checked by `build`.
+///
+/// ```
+/// use splr::config::{Config, ConfigError};
+///
+/// let config = Config::builder().rst_step(100).rst_luby_inc(1.5).build();
+/// assert_eq!(config.map(|c| c.rst_step), Ok(100));
+/// let config = Config::builder().rst_eagerness(0.0).build();
+/// assert_eq!(config.err(), Some(ConfigError::OutOfRange("rst_eagerness")));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+macro_rules! restart_setters {
+    ($($field: ident: $ty: ty,)*) => {
+        impl ConfigBuilder {
+            $(
+                #[doc = concat!("set `", stringify!($field), "`.")]
+                pub fn $field(mut self, val: $ty) -> Self {
+                    self.config.$field = val;
+                    self
+                }
+            )*
+        }
+    };
+}
+
+restart_setters! {
+    rst_lbd_seen_thr: u16,
+    rst_db_size_thr: f64,
+    rst_near_sol_thr: f64,
+    rst_on_rescale: bool,
+    rst_jitter: usize,
+    rst_reuse_thr: f64,
+    rst_mode_switch: bool,
+    rst_stb_budget: usize,
+    rst_bandit: bool,
+    rst_locality_thr: f64,
+    rst_strict: bool,
+    rst_epoch: usize,
+    rst_lbd_cap: u16,
+    rst_step: usize,
+    rst_partial: bool,
+    rst_stb_transition_restart: bool,
+    rst_hardness: bool,
+    rst_lvl_thr: f64,
+    rst_fixed_stage: bool,
+    rst_luby_inc: f64,
+    rst_never: bool,
+    rst_local_search: usize,
+    rst_eagerness: f64,
+    rst_vote_thr: f64,
+    rst_vote_lbd: f64,
+    rst_vote_ent: f64,
+    rst_vote_reuse: f64,
+    rst_stb_grace: usize,
+    rst_lbd_use_average: bool,
+    rst_rcc_thr: f64,
+    rst_min_interval: usize,
+    rst_geometric: bool,
+    rst_geometric_inc: f64,
+    rst_geometric_step: usize,
+    rst_reset_phase: bool,
+    rst_asg_adaptive: bool,
+    rst_max_count: usize,
+    rst_relax: f64,
+    rst_hybrid: bool,
+    rst_bkt_pwr: f64,
+    rst_bkt_thr: f64,
+    rst_bkt_decay: usize,
+    rst_stb_max: usize,
+    rst_lvl_force: bool,
+}
+
+impl From<Config> for ConfigBuilder {
+    fn from(config: Config) -> Self {
+        ConfigBuilder { config }
+    }
+}
+
+impl ConfigBuilder {
+    /// return the config if `Config::check_restart_options` accepts it.
+    pub fn build(self) -> Result<Config, ConfigError> {
+        self.config.check_restart_options()?;
+        Ok(self.config)
+    }
+}
+
 #[allow(unused_macros)]
 macro_rules! dispatch {
     // from `0` and `1`
@@ -385,6 +992,195 @@ macro_rules! dispatch {
 }
 
 impl Config {
+    /// return `true` if both have the same restart options (`rst_*`).
+    pub fn restart_eq(&self, other: &Config) -> bool {
+        self.rst_lbd_seen_thr == other.rst_lbd_seen_thr
+            && self.rst_db_size_thr == other.rst_db_size_thr
+            && self.rst_near_sol_thr == other.rst_near_sol_thr
+            && self.rst_on_rescale == other.rst_on_rescale
+            && self.rst_jitter == other.rst_jitter
+            && self.rst_reuse_thr == other.rst_reuse_thr
+            && self.rst_mode_switch == other.rst_mode_switch
+            && self.rst_stb_budget == other.rst_stb_budget
+            && self.rst_bandit == other.rst_bandit
+            && self.rst_locality_thr == other.rst_locality_thr
+            && self.rst_strict == other.rst_strict
+            && self.rst_epoch == other.rst_epoch
+            && self.rst_lbd_cap == other.rst_lbd_cap
+            && self.rst_step == other.rst_step
+            && self.rst_partial == other.rst_partial
+            && self.rst_stb_transition_restart == other.rst_stb_transition_restart
+            && self.rst_hardness == other.rst_hardness
+            && self.rst_lvl_thr == other.rst_lvl_thr
+            && self.rst_fixed_stage == other.rst_fixed_stage
+            && self.rst_luby_inc == other.rst_luby_inc
+            && self.rst_never == other.rst_never
+            && self.rst_local_search == other.rst_local_search
+            && self.rst_eagerness == other.rst_eagerness
+            && self.rst_vote_thr == other.rst_vote_thr
+            && self.rst_vote_lbd == other.rst_vote_lbd
+            && self.rst_vote_ent == other.rst_vote_ent
+            && self.rst_vote_reuse == other.rst_vote_reuse
+            && self.rst_stb_grace == other.rst_stb_grace
+            && self.rst_lbd_use_average == other.rst_lbd_use_average
+            && self.rst_rcc_thr == other.rst_rcc_thr
+            && self.rst_min_interval == other.rst_min_interval
+            && self.rst_geometric == other.rst_geometric
+            && self.rst_geometric_inc == other.rst_geometric_inc
+            && self.rst_geometric_step == other.rst_geometric_step
+            && self.rst_reset_phase == other.rst_reset_phase
+            && self.rst_asg_adaptive == other.rst_asg_adaptive
+            && self.rst_max_count == other.rst_max_count
+            && self.rst_relax == other.rst_relax
+            && self.rst_hybrid == other.rst_hybrid
+            && self.rst_bkt_pwr == other.rst_bkt_pwr
+            && self.rst_bkt_thr == other.rst_bkt_thr
+            && self.rst_bkt_decay == other.rst_bkt_decay
+            && self.rst_stb_max == other.rst_stb_max
+            && self.rst_lvl_force == other.rst_lvl_force
+    }
+    /// check the ranges and the required features of restart options (`rst_*`).
+    pub fn check_restart_options(&self) -> Result<(), ConfigError> {
+        if self.rst_db_size_thr < 0.0 {
+            return Err(ConfigError::OutOfRange("rst_db_size_thr"));
+        }
+        if self.rst_eagerness.is_nan() || self.rst_eagerness <= 0.0 {
+            return Err(ConfigError::OutOfRange("rst_eagerness"));
+        }
+        if self.rst_near_sol_thr < 0.0 {
+            return Err(ConfigError::OutOfRange("rst_near_sol_thr"));
+        }
+        if !(0.0..=1.0).contains(&self.rst_relax) {
+            return Err(ConfigError::OutOfRange("rst_relax"));
+        }
+        if self.rst_luby_inc.is_nan() || self.rst_luby_inc < 1.0 {
+            return Err(ConfigError::OutOfRange("rst_luby_inc"));
+        }
+        for (name, val) in [
+            ("rst_reuse_thr", self.rst_reuse_thr),
+            ("rst_locality_thr", self.rst_locality_thr),
+            ("rst_lvl_thr", self.rst_lvl_thr),
+            ("rst_rcc_thr", self.rst_rcc_thr),
+            ("rst_vote_thr", self.rst_vote_thr),
+            ("rst_vote_lbd", self.rst_vote_lbd),
+            ("rst_vote_ent", self.rst_vote_ent),
+            ("rst_vote_reuse", self.rst_vote_reuse),
+            ("rst_bkt_pwr", self.rst_bkt_pwr),
+        ] {
+            if val.is_nan() || val < 0.0 {
+                return Err(ConfigError::OutOfRange(name));
+            }
+        }
+        for (opt1, opt2, conflict) in [
+            (
+                "rst_never",
+                "rst_geometric",
+                self.rst_never && self.rst_geometric,
+            ),
+            ("rst_never", "rst_hybrid", self.rst_never && self.rst_hybrid),
+            (
+                "rst_never",
+                "rst_mode_switch",
+                self.rst_never && self.rst_mode_switch,
+            ),
+            ("rst_never", "rst_bandit", self.rst_never && self.rst_bandit),
+            (
+                "rst_geometric",
+                "rst_hybrid",
+                self.rst_geometric && self.rst_hybrid,
+            ),
+            (
+                "rst_bandit",
+                "rst_mode_switch",
+                self.rst_bandit && self.rst_mode_switch,
+            ),
+            (
+                "rst_geometric",
+                "rst_mode_switch",
+                self.rst_geometric && self.rst_mode_switch,
+            ),
+            (
+                "rst_geometric",
+                "rst_bandit",
+                self.rst_geometric && self.rst_bandit,
+            ),
+            (
+                "rst_hybrid",
+                "rst_mode_switch",
+                self.rst_hybrid && self.rst_mode_switch,
+            ),
+            (
+                "rst_hybrid",
+                "rst_bandit",
+                self.rst_hybrid && self.rst_bandit,
+            ),
+            (
+                "rst_never",
+                "rst_bkt_pwr",
+                self.rst_never && 0.0 < self.rst_bkt_pwr,
+            ),
+            (
+                "rst_geometric",
+                "rst_bkt_pwr",
+                self.rst_geometric && 0.0 < self.rst_bkt_pwr,
+            ),
+            (
+                "rst_hybrid",
+                "rst_bkt_pwr",
+                self.rst_hybrid && 0.0 < self.rst_bkt_pwr,
+            ),
+            (
+                "rst_bkt_pwr",
+                "rst_mode_switch",
+                0.0 < self.rst_bkt_pwr && self.rst_mode_switch,
+            ),
+            (
+                "rst_bkt_pwr",
+                "rst_bandit",
+                0.0 < self.rst_bkt_pwr && self.rst_bandit,
+            ),
+            (
+                "rst_never",
+                "rst_local_search",
+                self.rst_never && 0 < self.rst_local_search,
+            ),
+        ] {
+            if conflict {
+                return Err(ConfigError::Conflict(opt1, opt2));
+            }
+        }
+        if self.rst_geometric {
+            if self.rst_geometric_inc.is_nan() || self.rst_geometric_inc < 1.0 {
+                return Err(ConfigError::OutOfRange("rst_geometric_inc"));
+            }
+            if self.rst_geometric_step == 0 {
+                return Err(ConfigError::OutOfRange("rst_geometric_step"));
+            }
+        }
+        if 0.0 < self.rst_bkt_pwr && (self.rst_bkt_thr.is_nan() || self.rst_bkt_thr <= 0.0) {
+            return Err(ConfigError::OutOfRange("rst_bkt_thr"));
+        }
+        if 3 < self.rst_bkt_decay {
+            return Err(ConfigError::OutOfRange("rst_bkt_decay"));
+        }
+        if !cfg!(feature = "assign_rate") && 0.0 < self.rst_near_sol_thr {
+            return Err(ConfigError::FeatureRequired(
+                "rst_near_sol_thr",
+                "assign_rate",
+            ));
+        }
+        if !cfg!(feature = "assign_rate") && self.rst_asg_adaptive {
+            return Err(ConfigError::FeatureRequired(
+                "rst_asg_adaptive",
+                "assign_rate",
+            ));
+        }
+        Ok(())
+    }
+    /// return a builder to set restart options by chained calls.
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
     #[allow(unused_mut)]
     pub fn override_args(mut self) -> Config {
         self
@@ -418,3 +1214,90 @@ pub mod property {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_options() {
+        let dump = format!("{:?}", Config::default());
+        let fields = dump
+            .split([',', '{'])
+            .filter_map(|s| s.trim().split(':').next())
+            .filter(|s| s.starts_with("rst_"))
+            .collect::<Vec<&str>>();
+        assert_eq!(fields.len(), RESTART_OPTIONS.len());
+        for name in fields {
+            assert!(restart_option(name).is_some(), "no entry for {}", name);
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let config = Config::builder()
+            .rst_step(100)
+            .rst_lbd_seen_thr(6)
+            .rst_geometric(true)
+            .rst_geometric_inc(2.0)
+            .build()
+            .unwrap();
+        assert!(config.restart_eq(&Config {
+            rst_step: 100,
+            rst_lbd_seen_thr: 6,
+            rst_geometric: true,
+            rst_geometric_inc: 2.0,
+            ..Config::default()
+        }));
+        let config = Config::builder()
+            .rst_max_count(20)
+            .rst_relax(0.5)
+            .rst_hybrid(true)
+            .build()
+            .unwrap();
+        assert!(config.restart_eq(&Config {
+            rst_max_count: 20,
+            rst_relax: 0.5,
+            rst_hybrid: true,
+            ..Config::default()
+        }));
+        let base = Config {
+            c_timeout: 10.0,
+            ..Config::default()
+        };
+        let config = ConfigBuilder::from(base).rst_jitter(3).build().unwrap();
+        assert_eq!((config.c_timeout, config.rst_jitter), (10.0, 3));
+        assert_eq!(
+            Config::builder()
+                .rst_geometric(true)
+                .rst_geometric_step(0)
+                .build()
+                .err(),
+            Some(ConfigError::OutOfRange("rst_geometric_step"))
+        );
+        assert_eq!(
+            Config::builder().rst_luby_inc(f64::NAN).build().err(),
+            Some(ConfigError::OutOfRange("rst_luby_inc"))
+        );
+        assert_eq!(
+            Config::builder().rst_vote_thr(-1.0).build().err(),
+            Some(ConfigError::OutOfRange("rst_vote_thr"))
+        );
+    }
+
+    #[test]
+    fn test_restart_eq() {
+        let c1 = Config::default();
+        let c2 = Config {
+            c_timeout: 10.0,
+            elm_var_occ: 4,
+            ..Config::default()
+        };
+        assert!(c1.restart_eq(&c2));
+        let c3 = Config {
+            rst_stb_budget: 4,
+            ..Config::default()
+        };
+        assert!(!c1.restart_eq(&c3));
+    }
+}
@@ -72,8 +72,8 @@ pub mod types;
 
 pub use {
     config::Config,
-    primitive::{ema::*, luby::*},
-    solver::{Certificate, SatSolverIF, SolveIF, Solver, ValidateIF},
+    primitive::{ema::*, geometric::*, luby::*},
+    solver::{solve_batch, Certificate, SatSolverIF, SolveIF, Solver, ValidateIF},
     types::{PropertyDereference, PropertyReference, SolverError},
 };
 
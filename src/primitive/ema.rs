@@ -11,12 +11,21 @@ pub trait EmaIF {
     fn get_slow(&self) -> f64 {
         unimplemented!()
     }
-    /// return a ratio of short / long statistics.
+    /// return a ratio of short / long statistics, or the current value
+    /// if there is no long one.
     fn trend(&self) -> f64 {
-        unimplemented!()
+        self.get()
     }
 }
 
+/// API for the EMAs of search progress consulted by restart heuristics.
+pub trait ProgressEvaluatorIF: EmaIF {
+    /// return the name for logging.
+    fn name(&self) -> &'static str;
+    /// return `true` if it has been updated at least once.
+    fn is_touched(&self) -> bool;
+}
+
 pub trait EmaSingleIF: EmaIF {
     /// return the current value.
     fn get(&self) -> f64 {
@@ -34,13 +43,22 @@ pub trait EmaMutIF: EmaIF {
     fn reset_slow(&mut self) {}
     /// catch up with the current state.
     fn update(&mut self, x: Self::Input);
+    /// catch up with `weight` occurrences of the same input at once.
+    fn update_weighted(&mut self, x: Self::Input, weight: usize)
+    where
+        Self::Input: Copy,
+    {
+        for _ in 0..weight {
+            self.update(x);
+        }
+    }
     /// return a view.
     fn as_view(&self) -> &EmaView;
     /// set value.
     fn set_value(&mut self, _x: f64) {}
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EmaView {
     fast: f64,
     slow: f64,
@@ -54,16 +72,25 @@ impl EmaIF for EmaView {
         self.slow
     }
     fn trend(&self) -> f64 {
-        self.fast / self.slow
+        // The slow EMA is 0 until a nonzero input.
+        let t = self.fast / self.slow;
+        if t.is_finite() {
+            t
+        } else {
+            1.0
+        }
     }
 }
 
 /// Exponential Moving Average, with a calibrator if feature `EMA_calibration` is on.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Ema {
     val: EmaView,
     #[cfg(feature = "EMA_calibration")]
     cal: f64,
+    /// the weight sum of inputs, 1 - (1 - sca)^t, if built by `new_with_correction`
+    #[cfg(not(feature = "EMA_calibration"))]
+    cor: Option<f64>,
     sca: f64,
 }
 
@@ -74,13 +101,10 @@ impl EmaIF for Ema {
     }
     #[cfg(not(feature = "EMA_calibration"))]
     fn get_fast(&self) -> f64 {
-        self.val.fast
-    }
-    fn get_slow(&self) -> f64 {
-        unimplemented!()
-    }
-    fn trend(&self) -> f64 {
-        unimplemented!()
+        match self.cor {
+            Some(c) if 0.0 < c => self.val.fast / c,
+            _ => self.val.fast,
+        }
     }
 }
 
@@ -89,23 +113,47 @@ impl EmaMutIF for Ema {
     #[cfg(not(feature = "EMA_calibration"))]
     fn update(&mut self, x: Self::Input) {
         self.val.fast = self.sca * x + (1.0 - self.sca) * self.val.fast;
+        if let Some(c) = self.cor.as_mut() {
+            *c = self.sca + (1.0 - self.sca) * *c;
+        }
     }
     #[cfg(feature = "EMA_calibration")]
     fn update(&mut self, x: Self::Input) {
         self.val.fast = self.sca * x + (1.0 - self.sca) * self.val.fast;
         self.cal = self.sca + (1.0 - self.sca) * self.cal;
     }
+    #[cfg(not(feature = "EMA_calibration"))]
+    fn update_weighted(&mut self, x: Self::Input, weight: usize) {
+        let decay = (1.0 - self.sca).powi(weight.min(i32::MAX as usize) as i32);
+        self.val.fast = x + decay * (self.val.fast - x);
+        if let Some(c) = self.cor.as_mut() {
+            *c = 1.0 + decay * (*c - 1.0);
+        }
+    }
+    #[cfg(feature = "EMA_calibration")]
+    fn update_weighted(&mut self, x: Self::Input, weight: usize) {
+        let decay = (1.0 - self.sca).powi(weight.min(i32::MAX as usize) as i32);
+        self.val.fast = x + decay * (self.val.fast - x);
+        self.cal = 1.0 + decay * (self.cal - 1.0);
+    }
     fn as_view(&self) -> &EmaView {
         &self.val
     }
     fn set_value(&mut self, x: f64) {
         self.val.fast = x;
         self.val.slow = x;
+        #[cfg(not(feature = "EMA_calibration"))]
+        if let Some(c) = self.cor.as_mut() {
+            *c = 1.0;
+        }
     }
 }
 
 impl Ema {
+    /// make an EMA over a window of `s`. A window of 0 is taken as 1,
+    /// i.e. the EMA follows the last input.
     pub fn new(s: usize) -> Ema {
+        let s = s.max(1);
         Ema {
             val: EmaView {
                 fast: 0.0,
@@ -113,19 +161,48 @@ impl Ema {
             },
             #[cfg(feature = "EMA_calibration")]
             cal: 0.0,
+            #[cfg(not(feature = "EMA_calibration"))]
+            cor: None,
             sca: 1.0 / (s as f64),
         }
     }
+    /// clear the accumulated value, keeping the window length and bias correction.
+    pub fn reset(&mut self) {
+        self.val.fast = 0.0;
+        self.val.slow = 0.0;
+        #[cfg(feature = "EMA_calibration")]
+        {
+            self.cal = 0.0;
+        }
+        #[cfg(not(feature = "EMA_calibration"))]
+        if let Some(c) = self.cor.as_mut() {
+            *c = 0.0;
+        }
+    }
+    /// return an EMA which corrects the bias toward the initial 0, like Adam's
+    /// 1/(1-β^t), so that `get` is the weighted mean of inputs from the first one.
+    /// Views by `as_view` are not corrected.
+    /// Under feature `EMA_calibration`, every `Ema` is corrected in this way.
+    pub fn new_with_correction(s: usize) -> Ema {
+        #[cfg(not(feature = "EMA_calibration"))]
+        {
+            Ema {
+                cor: Some(0.0),
+                ..Ema::new(s)
+            }
+        }
+        #[cfg(feature = "EMA_calibration")]
+        Ema::new(s)
+    }
     /// set value.
     pub fn with_value(mut self, x: f64) -> Ema {
-        self.val.fast = x;
-        self.val.slow = x;
+        self.set_value(x);
         self
     }
 }
 
 /// Exponential Moving Average pair, with a calibrator if feature `EMA_calibration` is on.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Ema2 {
     ema: EmaView,
     #[cfg(feature = "EMA_calibration")]
@@ -177,6 +254,22 @@ impl EmaMutIF for Ema2 {
         self.calf = self.fe + (1.0 - self.fe) * self.calf;
         self.cals = self.se + (1.0 - self.se) * self.cals;
     }
+    #[cfg(not(feature = "EMA_calibration"))]
+    fn update_weighted(&mut self, x: Self::Input, weight: usize) {
+        let fd = (1.0 - self.fe).powi(weight.min(i32::MAX as usize) as i32);
+        let sd = (1.0 - self.se).powi(weight.min(i32::MAX as usize) as i32);
+        self.ema.fast = x + fd * (self.ema.fast - x);
+        self.ema.slow = x + sd * (self.ema.slow - x);
+    }
+    #[cfg(feature = "EMA_calibration")]
+    fn update_weighted(&mut self, x: Self::Input, weight: usize) {
+        let fd = (1.0 - self.fe).powi(weight.min(i32::MAX as usize) as i32);
+        let sd = (1.0 - self.se).powi(weight.min(i32::MAX as usize) as i32);
+        self.ema.fast = x + fd * (self.ema.fast - x);
+        self.ema.slow = x + sd * (self.ema.slow - x);
+        self.calf = 1.0 + fd * (self.calf - 1.0);
+        self.cals = 1.0 + sd * (self.cals - 1.0);
+    }
     fn reset_to(&mut self, val: f64) {
         self.ema.fast = val;
     }
@@ -204,7 +297,9 @@ impl EmaMutIF for Ema2 {
 }
 
 impl Ema2 {
+    /// `len` 0 is treated as 1, which makes the EMA follow the raw input.
     pub fn new(len: usize) -> Ema2 {
+        let len = len.max(1);
         Ema2 {
             ema: EmaView {
                 fast: 0.0,
@@ -218,9 +313,19 @@ impl Ema2 {
             se: 1.0 / (len as f64),
         }
     }
+    /// clear the accumulated values, keeping the window lengths.
+    pub fn reset(&mut self) {
+        self.ema.fast = 0.0;
+        self.ema.slow = 0.0;
+        #[cfg(feature = "EMA_calibration")]
+        {
+            self.calf = 0.0;
+            self.cals = 0.0;
+        }
+    }
     // set secondary EMA parameter
     pub fn with_slow(mut self, s: usize) -> Ema2 {
-        self.se = 1.0 / (s as f64);
+        self.se = 1.0 / (s.max(1) as f64);
         self
     }
     pub fn get_slow(&self) -> f64 {
@@ -237,6 +342,17 @@ impl Ema2 {
         }
         self
     }
+    /// set fast and slow values, to restore a saved state.
+    pub fn with_values(mut self, fast: f64, slow: f64) -> Self {
+        self.ema.fast = fast;
+        self.ema.slow = slow;
+        #[cfg(feature = "EMA_calibration")]
+        {
+            self.calf = 1.0;
+            self.cals = 1.0;
+        }
+        self
+    }
 }
 
 /// Ema of Sequence of usize
@@ -250,12 +366,6 @@ impl EmaIF for EmaSU {
     fn get_fast(&self) -> f64 {
         self.ema.get_fast()
     }
-    fn get_slow(&self) -> f64 {
-        unimplemented!()
-    }
-    fn trend(&self) -> f64 {
-        unimplemented!()
-    }
 }
 
 impl EmaMutIF for EmaSU {
@@ -324,10 +434,17 @@ impl<const N: usize> Ewa<N> {
             last: 0,
         }
     }
+    /// multiply the average and every term in the window by `factor`.
+    pub fn scale(&mut self, factor: f64) {
+        self.ema.fast *= factor;
+        for x in self.pool.iter_mut() {
+            *x *= factor;
+        }
+    }
 }
 
 /// Exponential Moving Average pair, with a calibrator if feature `EMA_calibration` is on.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Ewa2<const N: usize> {
     ema: EmaView,
     pool: [f64; N],
@@ -406,10 +523,189 @@ impl<const N: usize> Ewa2<N> {
             sx: 1.0 - 1.0 / (N as f64),
         }
     }
+    /// clear the accumulated values and the window, keeping the window lengths.
+    pub fn reset(&mut self) {
+        self.ema.fast = 0.0;
+        self.ema.slow = 0.0;
+        self.pool = [0.0; N];
+        self.last = 0;
+        #[cfg(feature = "EMA_calibration")]
+        {
+            self.cals = 0.0;
+        }
+    }
     // set secondary EMA parameter
     pub fn with_slow(mut self, s: usize) -> Self {
-        self.se = 1.0 / (s as f64);
+        self.se = 1.0 / (s.max(1) as f64);
         self.sx = 1.0 - self.se;
         self
     }
+    /// multiply the fast average and every term in its window by `factor`,
+    /// keeping the slow EMA.
+    pub fn scale_fast(&mut self, factor: f64) {
+        self.ema.fast *= factor;
+        for x in self.pool.iter_mut() {
+            *x *= factor;
+        }
+    }
+    /// set fast and slow values, to restore a saved state.
+    pub fn with_values(mut self, fast: f64, slow: f64) -> Self {
+        self.ema.fast = fast;
+        self.ema.slow = slow;
+        self.pool = [fast / N as f64; N];
+        #[cfg(feature = "EMA_calibration")]
+        {
+            self.cals = 1.0;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_update() {
+        let mut e1 = Ema::new(100).with_value(2.0);
+        let mut e2 = e1.clone();
+        for _ in 0..40 {
+            e1.update(5.0);
+        }
+        e2.update_weighted(5.0, 40);
+        assert!((e1.get() - e2.get()).abs() < 1e-9);
+
+        let mut e1 = Ema2::new(16).with_slow(1000).with_value(2.0);
+        let mut e2 = e1.clone();
+        for _ in 0..40 {
+            e1.update(5.0);
+        }
+        e2.update_weighted(5.0, 40);
+        assert!((e1.get_fast() - e2.get_fast()).abs() < 1e-9);
+        assert!((e1.get_slow() - e2.get_slow()).abs() < 1e-9);
+        assert!((e1.trend() - e2.trend()).abs() < 1e-9);
+        // a weight over `i32::MAX` doesn't wrap around to a negative exponent
+        let mut e = Ema2::new(16).with_slow(1000).with_value(2.0);
+        e.update_weighted(5.0, usize::MAX);
+        assert_eq!((e.get_fast(), e.get_slow()), (5.0, 5.0));
+        let mut e = Ema::new(16);
+        e.update_weighted(5.0, usize::MAX);
+        assert_eq!(e.get(), 5.0);
+    }
+
+    #[test]
+    fn test_view_trend() {
+        let e = Ema2::new(4).with_slow(16);
+        assert!(e.trend().is_nan());
+        assert_eq!(e.as_view().trend(), 1.0);
+        let view = EmaView {
+            fast: 2.0,
+            slow: 0.0,
+        };
+        assert_eq!(view.trend(), 1.0);
+        let mut e = Ema2::new(4).with_slow(16);
+        e.update(2.0);
+        assert_eq!(e.as_view().trend(), e.trend());
+    }
+
+    #[test]
+    fn test_reset() {
+        let pristine = Ema2::new(4).with_slow(16);
+        let mut e = pristine.clone();
+        for i in 0..100 {
+            e.update(i as f64);
+        }
+        assert!(0.0 < e.get());
+        e.reset();
+        assert_eq!(e, pristine);
+        assert_eq!(e.get(), 0.0);
+        assert!(e.trend().is_nan());
+        e.update(2.0);
+        let mut fresh = pristine.clone();
+        fresh.update(2.0);
+        assert_eq!(e.get(), fresh.get());
+        assert_eq!(e.trend(), fresh.trend());
+        let mut e = Ema::new_with_correction(8);
+        e.update(3.0);
+        e.reset();
+        assert_eq!(e.get(), 0.0);
+        e.update(5.0);
+        assert!((e.get() - 5.0).abs() < 1e-9);
+        let pristine = Ewa2::<4>::new(0.0).with_slow(16);
+        let mut e = pristine.clone();
+        for i in 0..10 {
+            e.update(i as f64);
+        }
+        e.reset();
+        assert_eq!(e, pristine);
+    }
+
+    #[test]
+    fn test_zero_length_window() {
+        let mut e = Ema::new(0);
+        let mut c = Ema::new_with_correction(0);
+        for x in [3.0, 5.0] {
+            e.update(x);
+            c.update(x);
+            assert_eq!(e.get(), x);
+            assert_eq!(c.get(), x);
+        }
+        // `Ema` has no slow EMA, and its trend is its value
+        assert_eq!(e.trend(), e.get());
+    }
+
+    #[test]
+    fn test_bias_correction() {
+        let mut plain = Ema::new(100);
+        let mut corrected = Ema::new_with_correction(100);
+        for _ in 0..10 {
+            plain.update(4.0);
+            corrected.update(4.0);
+            assert!((corrected.get() - 4.0).abs() < 1e-9);
+        }
+        #[cfg(not(feature = "EMA_calibration"))]
+        assert!(plain.get() < 0.5 * corrected.get());
+        let mut weighted = Ema::new_with_correction(100);
+        weighted.update_weighted(4.0, 10);
+        assert!((weighted.get() - 4.0).abs() < 1e-9);
+        for _ in 0..2_000 {
+            plain.update(2.0);
+            corrected.update(2.0);
+        }
+        assert!((plain.get() - corrected.get()).abs() < 1e-6);
+        let e = Ema::new_with_correction(100).with_value(3.0);
+        assert_eq!(e.get(), 3.0);
+    }
+
+    #[test]
+    fn test_with_values() {
+        let e = Ema2::new(16).with_slow(1000).with_values(3.0, 2.0);
+        assert_eq!(e.get_fast(), 3.0);
+        assert_eq!(e.get_slow(), 2.0);
+        assert_eq!(e.trend(), 1.5);
+
+        let mut e = Ewa2::<4>::new(0.0).with_slow(1000).with_values(3.0, 2.0);
+        assert_eq!(e.get_fast(), 3.0);
+        assert_eq!(e.trend(), 1.5);
+        for _ in 0..4 {
+            e.update(3.0);
+        }
+        assert!((e.get_fast() - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_window_length_one() {
+        let mut e = Ema2::new(1).with_slow(1);
+        let mut w = Ewa2::<1>::new(0.0).with_slow(1);
+        let mut z = Ema2::new(0).with_slow(0);
+        for lbd in [2.0, 8.0, 3.0, 30.0, 2.0] {
+            e.update(lbd);
+            w.update(lbd);
+            z.update(lbd);
+            assert_eq!(e.get(), lbd);
+            assert!(e.trend().is_finite());
+            assert!(w.trend().is_finite());
+            assert_eq!((z.get(), z.trend()), (e.get(), e.trend()));
+        }
+    }
 }
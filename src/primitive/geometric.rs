@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// The geometric series of restart intervals `step`, `step * inc`, `step * inc^2`, ...
+/// as MiniSat uses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeometricSeries {
+    index: usize,
+    /// the next interval before truncation
+    next_restart: f64,
+    /// the ratio of consecutive intervals
+    restart_inc: f64,
+    /// the first interval
+    step: usize,
+}
+
+impl Default for GeometricSeries {
+    fn default() -> Self {
+        GeometricSeries::new(100, 1.5)
+    }
+}
+
+impl fmt::Display for GeometricSeries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Geometric[index:{}]", self.index)
+    }
+}
+
+impl GeometricSeries {
+    pub fn new(step: usize, restart_inc: f64) -> Self {
+        GeometricSeries {
+            index: 0,
+            next_restart: step as f64,
+            restart_inc,
+            step,
+        }
+    }
+    /// return the next interval, truncated to an integer.
+    pub fn next_unchecked(&mut self) -> usize {
+        let val = self.next_restart as usize;
+        self.index += 1;
+        self.next_restart *= self.restart_inc;
+        val
+    }
+    pub fn reset(&mut self) {
+        self.index = 0;
+        self.next_restart = self.step as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geometric_series() {
+        let mut geo = GeometricSeries::new(100, 1.5);
+        let v = vec![100, 150, 225, 337, 506, 759, 1139, 1708];
+        let l = v
+            .iter()
+            .map(|_| geo.next_unchecked())
+            .collect::<Vec<usize>>();
+        assert_eq!(l, v);
+        geo.reset();
+        assert_eq!(geo.next_unchecked(), 100);
+    }
+}
@@ -1,10 +1,17 @@
-use std::{fmt, num::NonZeroU32};
+use {
+    crate::types::EmaIF,
+    std::{fmt, num::NonZeroU32},
+};
 
-#[derive(Clone, Debug)]
+/// The Luby series 1, 2, 1, 1, 2, 4, ..., i.e. the classic one without its first term,
+/// generated by Knuth's reluctant doubling in O(1) per step.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct LubySeries {
     index: usize,
-    seq: isize,
-    size: usize,
+    /// the number of the current run of doubling, from 1
+    u: usize,
+    /// the current value, doubled in a run and reset to 1 when `u & -u == v`
+    v: usize,
     max_value: usize,
 }
 
@@ -12,8 +19,8 @@ impl Default for LubySeries {
     fn default() -> Self {
         LubySeries {
             index: 0,
-            seq: 0,
-            size: 1,
+            u: 1,
+            v: 1,
             max_value: 1,
         }
     }
@@ -25,64 +32,57 @@ impl fmt::Display for LubySeries {
     }
 }
 
+impl EmaIF for LubySeries {
+    /// return the last value of the series, or 1 before the first.
+    fn get_fast(&self) -> f64 {
+        self.v as f64
+    }
+}
+
 impl Iterator for LubySeries {
     type Item = NonZeroU32;
-    /// Find the finite subsequence that contains index 'x', and the
-    /// size of that subsequence as: 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8
+    /// return the next value of: 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8,
+    /// without the first term.
     fn next(&mut self) -> Option<NonZeroU32> {
-        self.index += 1;
-        let mut seq = self.seq;
-        let mut size = self.size;
-        while size < self.index + 1 {
-            self.seq = seq;
-            seq += 1;
-            self.size = size;
-            size = 2 * size + 1;
-        }
-        let mut index = self.index;
-        while size - 1 != index {
-            size = (size - 1) >> 1;
-            seq -= 1;
-            index %= size;
-        }
-        let val = 2usize.pow(seq as u32);
-        if self.max_value < val {
-            self.max_value = val;
-        }
-        NonZeroU32::new(val as u32)
+        NonZeroU32::new(1 << self.next_exponent())
     }
 }
 
 impl LubySeries {
     pub fn next_unchecked(&mut self) -> usize {
+        2usize.pow(self.next_exponent())
+    }
+    /// return the next value of the series where `base` replaces 2,
+    /// e.g. 1, 1.5, 1, 1, 1.5, 2.25, ... for base 1.5.
+    /// `max_value` still holds the maximum of the series in base 2.
+    pub fn next_with_base(&mut self, base: f64) -> f64 {
+        base.powi(self.next_exponent() as i32)
+    }
+    /// advance the series and return the exponent of the next value.
+    fn next_exponent(&mut self) -> u32 {
         self.index += 1;
-        let mut seq = self.seq;
-        let mut size = self.size;
-        while size < self.index + 1 {
-            self.seq = seq;
-            seq += 1;
-            self.size = size;
-            size = 2 * size + 1;
-        }
-        let mut index = self.index;
-        while size - 1 != index {
-            size = (size - 1) >> 1;
-            seq -= 1;
-            index %= size;
+        if self.u & self.u.wrapping_neg() == self.v {
+            self.u += 1;
+            self.v = 1;
+        } else {
+            self.v *= 2;
         }
-        let val = 2usize.pow(seq as u32);
-        if self.max_value < val {
-            self.max_value = val;
+        if self.max_value < self.v {
+            self.max_value = self.v;
         }
-        val
+        self.v.trailing_zeros()
+    }
+    /// return the number of values generated since the last reset.
+    pub fn index(&self) -> usize {
+        self.index
     }
     pub fn max_value(&self) -> usize {
         self.max_value
     }
     pub fn reset(&mut self) {
         self.index = 0;
-        self.seq = 0;
-        self.size = 1;
+        self.u = 1;
+        self.v = 1;
     }
 }
 
@@ -100,4 +100,60 @@ mod tests {
             .collect::<Vec<usize>>();
         assert_eq!(l, v);
     }
+
+    #[test]
+    fn test_luby_series_by_definition() {
+        // the classic series from index 1, by its recursive definition
+        fn luby(mut i: usize) -> usize {
+            loop {
+                let mut k = 1;
+                while (1 << k) - 1 < i {
+                    k += 1;
+                }
+                if i == (1 << k) - 1 {
+                    return 1 << (k - 1);
+                }
+                i -= (1 << (k - 1)) - 1;
+            }
+        }
+        let mut luby_series = LubySeries::default();
+        let mut max = 1;
+        for i in 2..=1001 {
+            let val = luby(i);
+            assert_eq!(luby_series.next_unchecked(), val);
+            max = max.max(val);
+            assert_eq!(luby_series.max_value(), max);
+        }
+        luby_series.reset();
+        let head = luby_series
+            .by_ref()
+            .take(6)
+            .map(|v| v.get())
+            .collect::<Vec<_>>();
+        assert_eq!(head, [1, 2, 1, 1, 2, 4]);
+        assert_eq!(luby_series.max_value(), max);
+    }
+
+    #[test]
+    fn test_trend() {
+        let mut luby = LubySeries::default();
+        assert_eq!(luby.trend(), 1.0);
+        for _ in 0..6 {
+            let v = luby.next_unchecked();
+            assert_eq!(luby.get(), v as f64);
+            assert_eq!(luby.trend(), luby.get());
+        }
+    }
+
+    #[test]
+    fn test_luby_series_with_base() {
+        let mut luby = LubySeries::default();
+        let v = [
+            1.0, 1.5, 1.0, 1.0, 1.5, 2.25, 1.0, 1.0, 1.5, 1.0, 1.0, 1.5, 2.25, 3.375,
+        ];
+        for x in v {
+            assert_eq!(luby.next_with_base(1.5), x);
+        }
+        assert_eq!(luby.max_value(), 8);
+    }
 }
@@ -1,6 +1,8 @@
 /// methods on clause activity
 pub mod ema;
+/// geometric series of restart intervals
+pub mod geometric;
 /// methods on binary link, namely binary clause
 pub mod luby;
 
-pub use self::{ema::*, luby::*};
+pub use self::{ema::*, geometric::*, luby::*};
@@ -93,6 +93,31 @@ pub trait SatSolverIF: Instantiate {
     /// assert_eq!(s.solve(), Ok(Certificate::SAT(vec![1, 2, 3, 4, 5, -6, 7, 8, -9])));
     /// ```
     fn add_var(&mut self) -> VarId;
+    /// add clauses read from a DIMACS stream one by one, without buffering them.
+    /// A clause may span lines up to its terminating `0`.
+    /// A header `p cnf V C` adds vars up to `V`; a line starting with `%` ends the stream.
+    ///
+    /// # Errors
+    ///
+    /// * `SolverError::ParseError` with the line number if a line has a non-integer token.
+    /// * `SolverError::OutOfRange` if a literal in it is out of range for var index.
+    /// * `SolverError::EmptyClause` if a clause is empty or falsified by existing assignments.
+    /// * `SolverError::IOError` if it failed to read.
+    ///
+    /// # Example
+    ///```
+    /// use crate::splr::*;
+    /// use crate::splr::types::*;
+    ///
+    /// let mut s = Solver::instantiate(&Config::default(), &CNFDescription::default());
+    /// let dimacs = "c a sample\np cnf 3 3\n1 -2 0\n2 3\n0\n-1 -3 0\n";
+    /// assert!(s.add_from_reader(dimacs.as_bytes()).is_ok());
+    /// assert_eq!(s.asg.num_vars, 3);
+    /// assert!(matches!(s.add_from_reader("1 y 0\n".as_bytes()), Err(SolverError::ParseError(1))));
+    /// assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+    ///```
+    #[cfg(not(feature = "no_IO"))]
+    fn add_from_reader<R: BufRead>(&mut self, reader: R) -> Result<&mut Solver, SolverError>;
     #[cfg(not(feature = "no_IO"))]
     /// make a solver and load a CNF into it.
     ///
@@ -211,6 +236,45 @@ impl SatSolverIF for Solver {
         state.handle(SolverEvent::NewVar);
         asg.num_vars as VarId
     }
+    #[cfg(not(feature = "no_IO"))]
+    fn add_from_reader<R: BufRead>(&mut self, reader: R) -> Result<&mut Solver, SolverError> {
+        let mut clause: Vec<i32> = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.map_err(|_| SolverError::IOError)?;
+            let mut iter = line.split_whitespace();
+            match iter.clone().next() {
+                None => continue,
+                Some(s) if s.starts_with('c') => continue,
+                Some(s) if s.starts_with('%') => break,
+                Some("p") => {
+                    let nv = match (iter.nth(2), iter.next()) {
+                        (Some(v), Some(_)) => v.parse::<usize>().ok(),
+                        _ => None,
+                    }
+                    .ok_or(SolverError::ParseError(i + 1))?;
+                    while self.asg.num_vars < nv {
+                        self.add_var();
+                    }
+                    continue;
+                }
+                _ => (),
+            }
+            for s in iter {
+                match s.parse::<i32>() {
+                    Ok(0) => {
+                        self.add_clause(&clause)?;
+                        clause.clear();
+                    }
+                    Ok(val) => clause.push(val),
+                    Err(_) => return Err(SolverError::ParseError(i + 1)),
+                }
+            }
+        }
+        if !clause.is_empty() {
+            self.add_clause(&clause)?;
+        }
+        Ok(self)
+    }
     /// # Examples
     ///
     /// ```
@@ -401,6 +465,41 @@ mod tests {
     use crate::*;
     use std::path::Path;
 
+    #[cfg(not(feature = "no_IO"))]
+    #[test]
+    fn test_add_from_reader() {
+        use crate::types::*;
+        use std::{fs::File, io::BufReader};
+        for (file, sat) in [("cnfs/uf100-010.cnf", true), ("cnfs/unsat.cnf", false)] {
+            let config = Config::from(file);
+            let mut buffered = Solver::build(&config).expect("can't load");
+            let mut streamed = Solver::instantiate(&config, &CNFDescription::default());
+            let reader = BufReader::new(File::open(file).expect("can't open"));
+            if let Err(e) = streamed.add_from_reader(reader) {
+                // a conflict found while loading means UNSAT.
+                assert!(!sat && matches!(e, SolverError::EmptyClause), "{file}: {e}");
+                continue;
+            }
+            assert_eq!(streamed.asg.num_vars, buffered.asg.num_vars);
+            let result = streamed.solve();
+            assert_eq!(matches!(result, Ok(Certificate::SAT(_))), sat);
+            assert_eq!(
+                std::mem::discriminant(&result),
+                std::mem::discriminant(&buffered.solve())
+            );
+        }
+        let mut s = Solver::instantiate(&Config::default(), &CNFDescription::default());
+        let malformed = "p cnf 3 2\n1 -2 0\n2 x 0\n";
+        assert!(matches!(
+            s.add_from_reader(malformed.as_bytes()),
+            Err(SolverError::ParseError(3))
+        ));
+        assert!(matches!(
+            s.add_from_reader("p cnf three 1\n".as_bytes()),
+            Err(SolverError::ParseError(1))
+        ));
+    }
+
     #[cfg(not(feature = "no_IO"))]
     #[test]
     fn test_add_var() {
@@ -4,7 +4,7 @@
 use crate::assign::DebugReportIF;
 
 use {
-    super::State,
+    super::{RestartIF, State},
     crate::{
         assign::{AssignIF, AssignStack, PropagateIF, VarManipulateIF},
         cdb::{ClauseDB, ClauseDBIF},
@@ -77,9 +77,15 @@ pub fn handle_conflict(
     }
 
     asg.handle(SolverEvent::Conflict);
+    state.restart.update_reuse(match cc.1 {
+        AssignReason::Implication(cid) => Some(cid),
+        _ => None,
+    });
+    state.restart.update_level(asg.decision_level());
 
     state.derive20.clear();
     let assign_level = conflict_analyze(asg, cdb, state, cc).max(asg.root_level());
+    state.restart.update_locality(&state.new_learnt);
     let new_learnt = &mut state.new_learnt;
     let learnt_len = new_learnt.len();
     if learnt_len == 0 {
@@ -210,6 +216,7 @@ pub fn handle_conflict(
                 assign_level,
             );
             // || check_graph(asg, cdb, l0, "clause");
+            state.restart.add_learnt(cid);
             rank = cdb[cid].rank;
             if rank <= 20 {
                 for cid in &state.derive20 {
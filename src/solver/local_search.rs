@@ -0,0 +1,251 @@
+//! Module `local_search` improves saved phases by a WalkSAT-like local search
+//! on the irredundant clauses, run at restarts.
+use crate::{
+    assign::{AssignIF, AssignStack, VarManipulateIF},
+    cdb::{ClauseDB, ClauseDBIF, ClauseIF},
+    types::*,
+};
+
+/// the probability, in 1/1024, to flip a random var instead of the best one
+const NOISE: u64 = 512;
+
+/// A local search runner which keeps its own random state.
+#[derive(Clone, Debug, Default)]
+pub struct LocalSearch {
+    /// the number of flips per run (0: disabled)
+    flips: usize,
+    rng: u64,
+    /// the irredundant clauses under the root level assignment, without fixed vars
+    clauses: Vec<Vec<Lit>>,
+    /// the indices of clauses in `clauses` containing each literal
+    occurs: Vec<Vec<usize>>,
+    /// the numbers of root level assigned and eliminated vars at which `clauses`
+    /// were built, or `None` if they must be rebuilt
+    built_at: Option<(usize, usize)>,
+    pub num_run: usize,
+    /// the number of runs which reduced the falsified clauses
+    pub num_improved: usize,
+}
+
+impl Instantiate for LocalSearch {
+    fn instantiate(config: &Config, _cnf: &CNFDescription) -> Self {
+        LocalSearch {
+            flips: config.rst_local_search,
+            rng: config.c_seed.max(1),
+            ..LocalSearch::default()
+        }
+    }
+}
+
+impl LocalSearch {
+    pub fn is_enabled(&self) -> bool {
+        0 < self.flips
+    }
+    /// run local search from the saved phases under the root level assignment,
+    /// and save the best assignment found as the new phases.
+    /// Return the number of clauses falsified by the new phases.
+    pub fn improve_phases(&mut self, asg: &mut AssignStack, cdb: &ClauseDB) -> usize {
+        debug_assert_eq!(asg.decision_level(), asg.root_level());
+        self.num_run += 1;
+        let num_vars = asg.num_vars;
+        // `None` for vars fixed at the root level or eliminated.
+        let mut phase: Vec<Option<bool>> = (0..=num_vars)
+            .map(|vi| {
+                (0 < vi && asg.assign(vi).is_none() && !asg.var(vi).is(FlagVar::ELIMINATED))
+                    .then(|| asg.var(vi).is(FlagVar::PHASE))
+            })
+            .collect();
+        self.build_clauses(asg, cdb, &phase);
+        let clauses = &self.clauses;
+        let occurs = &self.occurs;
+        let value = |phase: &[Option<bool>], l: Lit| phase[l.vi()] == Some(bool::from(l));
+        let mut num_true: Vec<usize> = clauses
+            .iter()
+            .map(|c| c.iter().filter(|l| value(&phase, **l)).count())
+            .collect();
+        let mut falsified: Vec<usize> = (0..clauses.len()).filter(|i| num_true[*i] == 0).collect();
+        let initial = falsified.len();
+        let mut best = initial;
+        let mut best_phase = phase.clone();
+        let mut rng = self.rng;
+        for _ in 0..self.flips {
+            if falsified.is_empty() {
+                break;
+            }
+            let ci = falsified[(next_random(&mut rng) as usize) % falsified.len()];
+            // the number of clauses which flipping a var makes falsified.
+            let break_count = |vi: VarId| {
+                let l = Lit::from((vi, !phase[vi].unwrap_or(false)));
+                occurs[usize::from(l)]
+                    .iter()
+                    .filter(|cj| num_true[**cj] == 1)
+                    .count()
+            };
+            let c = &clauses[ci];
+            let vi = if next_random(&mut rng) % 1024 < NOISE {
+                c[(next_random(&mut rng) as usize) % c.len()].vi()
+            } else {
+                c.iter()
+                    .map(|l| l.vi())
+                    .min_by_key(|vi| break_count(*vi))
+                    .unwrap()
+            };
+            let b = !phase[vi].unwrap_or(false);
+            phase[vi] = Some(b);
+            for cj in &occurs[usize::from(Lit::from((vi, b)))] {
+                num_true[*cj] += 1;
+            }
+            for cj in &occurs[usize::from(Lit::from((vi, !b)))] {
+                num_true[*cj] -= 1;
+            }
+            falsified.retain(|cj| num_true[*cj] == 0);
+            for cj in &occurs[usize::from(Lit::from((vi, !b)))] {
+                if num_true[*cj] == 0 {
+                    falsified.push(*cj);
+                }
+            }
+            if falsified.len() < best {
+                best = falsified.len();
+                best_phase.clone_from(&phase);
+            }
+        }
+        self.rng = rng;
+        if best < initial {
+            self.num_improved += 1;
+        }
+        for (vi, b) in best_phase.iter().enumerate() {
+            if let Some(b) = b {
+                asg.var_mut(vi).set(FlagVar::PHASE, *b);
+            }
+        }
+        best
+    }
+    /// drop the cached clauses, which must be called after the clause database
+    /// is simplified.
+    pub fn invalidate(&mut self) {
+        self.built_at = None;
+    }
+    /// (re)build `clauses` and `occurs` unless nothing was assigned at the root
+    /// level or eliminated since the last build.
+    fn build_clauses(&mut self, asg: &AssignStack, cdb: &ClauseDB, phase: &[Option<bool>]) {
+        let key = (
+            asg.num_asserted_vars + asg.stack_len(),
+            asg.num_eliminated_vars,
+        );
+        if self.built_at == Some(key) {
+            return;
+        }
+        self.built_at = Some(key);
+        self.clauses.clear();
+        for c in cdb.iter().skip(1) {
+            if c.is_dead() || c.is(FlagClause::LEARNT) {
+                continue;
+            }
+            if c.iter().any(|l| asg.assigned(*l) == Some(true)) {
+                continue;
+            }
+            let lits = c
+                .iter()
+                .filter(|l| phase[l.vi()].is_some())
+                .copied()
+                .collect::<Vec<Lit>>();
+            if !lits.is_empty() {
+                self.clauses.push(lits);
+            }
+        }
+        self.occurs = vec![Vec::new(); 2 * (asg.num_vars + 1)];
+        for (ci, c) in self.clauses.iter().enumerate() {
+            for l in c {
+                self.occurs[usize::from(*l)].push(ci);
+            }
+        }
+    }
+}
+
+/// xorshift64
+fn next_random(rng: &mut u64) -> u64 {
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 7;
+    *rng ^= *rng << 17;
+    *rng
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assign::PropagateIF, solver::Solver};
+
+    #[test]
+    fn test_improve_phases() {
+        let config = Config {
+            rst_local_search: 1_000,
+            ..Config::default()
+        };
+        let cnf = vec![
+            vec![1, 2, -3],
+            vec![-1, 2, 4],
+            vec![3, -4, 5],
+            vec![-2, -5, 6],
+            vec![1, 5, 6],
+            vec![2, 3, 7],
+            vec![-6, 7, 8],
+            vec![4, 6, 8],
+        ];
+        let Ok(mut s) = Solver::try_from((config.clone(), cnf.as_ref())) else {
+            panic!("failed to build");
+        };
+        for vi in 1..=s.asg.num_vars {
+            s.asg.var_mut(vi).set(FlagVar::PHASE, false);
+        }
+        let mut ls = LocalSearch::instantiate(&config, &CNFDescription::default());
+        assert!(ls.is_enabled());
+        assert_eq!(ls.improve_phases(&mut s.asg, &s.cdb), 0);
+        assert!((1..=s.asg.num_vars).any(|vi| s.asg.var(vi).is(FlagVar::PHASE)));
+        let model = (0..=s.asg.num_vars)
+            .map(|vi| Some(s.asg.var(vi).is(FlagVar::PHASE)))
+            .collect::<Vec<_>>();
+        assert_eq!(s.cdb.validate(&model, false), None);
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_local_search_at_restarts() {
+        use crate::solver::{Certificate, SatSolverIF, SolveIF};
+        let mut config = Config::from("cnfs/uf100-010.cnf");
+        config.rst_local_search = 100;
+        let mut s = Solver::build(&config).expect("can't load");
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        assert!(0 < s.state.local_search.num_run);
+        assert!(0 < s.state.local_search.num_improved);
+    }
+
+    #[test]
+    fn test_clause_cache() {
+        let config = Config {
+            rst_local_search: 1_000,
+            ..Config::default()
+        };
+        let cnf = vec![vec![1, 2], vec![-1, 3], vec![-2, -3], vec![2, 3]];
+        let Ok(mut s) = Solver::try_from((config.clone(), cnf.as_ref())) else {
+            panic!("failed to build");
+        };
+        let mut ls = LocalSearch::instantiate(&config, &CNFDescription::default());
+        assert_eq!(ls.improve_phases(&mut s.asg, &s.cdb), 0);
+        assert_eq!(ls.clauses.len(), 4);
+        // a root level assignment satisfies or shortens clauses
+        s.asg
+            .assign_at_root_level(Lit::from(2i32))
+            .expect("conflict");
+        assert_eq!(ls.improve_phases(&mut s.asg, &s.cdb), 0);
+        assert_eq!(
+            ls.clauses,
+            vec![
+                vec![Lit::from(-1i32), Lit::from(3i32)],
+                vec![Lit::from(-3i32)]
+            ]
+        );
+        assert!(!s.asg.var(3).is(FlagVar::PHASE));
+        assert!(!s.asg.var(1).is(FlagVar::PHASE));
+        ls.invalidate();
+        assert_eq!(ls.built_at, None);
+    }
+}
@@ -3,24 +3,47 @@
 mod build;
 /// Crate 'conflict' handles conflicts.
 mod conflict;
+/// Crate `local_search` improves phases by local search at restarts.
+mod local_search;
 /// Crate `restart` provides restart heuristics.
 pub mod restart;
 /// CDCL search engine
 mod search;
 /// Stage manger (was Stabilizer)
 mod stage;
+/// Crate `trace` records and replays inputs to restart heuristics.
+pub mod trace;
 /// Crate `validate` implements a model checker.
 mod validate;
 
 pub use self::{
     build::SatSolverIF,
-    restart::{RestartIF, RestartManager},
+    local_search::LocalSearch,
+    restart::{
+        RestartIF, RestartManager, RestartMode, RestartReason, RestartReport, RestartSnapshot,
+        RestartStats,
+    },
     search::SolveIF,
     stage::StageManager,
+    trace::{RestartAudit, RestartInput, RestartTrace},
     validate::ValidateIF,
 };
 
-use crate::{assign::AssignStack, cdb::ClauseDB, state::*, types::*};
+use crate::{
+    assign::{self, AssignStack, VarManipulateIF},
+    cdb::ClauseDB,
+    cnf::CNF,
+    state::*,
+    types::*,
+};
+use std::{
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+};
 
 /// Normal results returned by Solver.
 #[derive(Debug, Eq, PartialEq)]
@@ -41,6 +64,8 @@ pub type SolverResult = Result<Certificate, SolverError>;
 /// define sub-modules' responsibilities
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum SolverEvent {
+    /// rescaling var activities.
+    ActivityRescale,
     /// asserting a var.
     Assert(VarId),
     /// conflict by unit propagation.
@@ -55,6 +80,8 @@ pub enum SolverEvent {
     Reinitialize,
     /// restart
     Restart,
+    /// reset saved phases, following a restart by `rst_reset_phase`.
+    ResetPhase,
     /// start a new stage of Luby stabilization. It holds new scale.
     Stage(usize),
 
@@ -99,6 +126,221 @@ impl<V: AsRef<[i32]>> TryFrom<Vec<V>> for Certificate {
     }
 }
 
+/// solve each of `instances` with a fresh solver built by `config`.
+/// With feature `parallel`, instances are solved by as many threads as
+/// `std::thread::available_parallelism`, each taking the next unsolved one.
+pub fn solve_batch(instances: Vec<CNF>, config: &Config) -> Vec<SolverResult> {
+    let solve = |cnf: &CNF| {
+        let clauses = cnf.to_vec();
+        match Solver::try_from((config.clone(), clauses.as_slice())) {
+            Ok(mut s) => s.solve(),
+            Err(result) => result,
+        }
+    };
+    if cfg!(feature = "parallel") {
+        let num_worker = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(instances.len());
+        let next = AtomicUsize::new(0);
+        let mut results = (0..instances.len())
+            .map(|_| Err(SolverError::UndescribedError))
+            .collect::<Vec<SolverResult>>();
+        std::thread::scope(|scope| {
+            let workers = (0..num_worker)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut solved = Vec::new();
+                        loop {
+                            let i = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(cnf) = instances.get(i) else {
+                                return solved;
+                            };
+                            // a panic fails its instance only, as with a thread per instance
+                            let result = std::panic::catch_unwind(AssertUnwindSafe(|| solve(cnf)))
+                                .unwrap_or(Err(SolverError::UndescribedError));
+                            solved.push((i, result));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            for (i, result) in workers
+                .into_iter()
+                .flat_map(|h| h.join().unwrap_or_default())
+            {
+                results[i] = result;
+            }
+        });
+        results
+    } else {
+        instances.iter().map(solve).collect()
+    }
+}
+
+/// format `val` as a Prometheus sample value, which spells non-finite ones
+/// `NaN`, `+Inf` and `-Inf`.
+fn prometheus_value(val: f64) -> String {
+    if val.is_nan() {
+        "NaN".to_string()
+    } else if val == f64::INFINITY {
+        "+Inf".to_string()
+    } else if val == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        val.to_string()
+    }
+}
+
+impl Solver {
+    /// send a [`SolverStats`] snapshot to `tx` every `state.stats_interval` conflicts.
+    pub fn with_stats_channel(mut self, tx: Sender<SolverStats>) -> Solver {
+        self.state.stats_channel = Some(tx);
+        self
+    }
+    /// let `controller` decide restarts at each conflict, instead of the restart
+    /// heuristics, which keep observing conflicts. The solver still restarts at
+    /// stage transitions, since clause DB reduction needs the root level.
+    pub fn with_restart_controller(
+        mut self,
+        controller: impl FnMut(&SolverStats) -> bool + Send + 'static,
+    ) -> Solver {
+        self.state.restart_controller = Some(RestartController(Arc::new(Mutex::new(controller))));
+        self
+    }
+    /// return a heuristic completion estimate in [0, 1), for progress bars.
+    /// SAT solving has no true progress measure; this combines the ratio of
+    /// fixed vars with the maturity of restart statistics and stages.
+    pub fn progress(&self) -> f64 {
+        let num_var = self.asg.derefer(assign::property::Tusize::NumVar) as f64;
+        if num_var == 0.0 {
+            return 0.0;
+        }
+        let fixed = (self.asg.derefer(assign::property::Tusize::NumAssertedVar)
+            + self.asg.derefer(assign::property::Tusize::NumEliminatedVar))
+            as f64
+            / num_var;
+        let (_, scale, _) = self.state.restart.stabilizer_state();
+        let maturity = self.state.restart.confidence() * (1.0 - 1.0 / (1.0 + scale as f64));
+        (fixed + (1.0 - fixed) * 0.5 * maturity) * num_var / (num_var + 1.0)
+    }
+    /// return (name, enabled, touched) of each restart evaluator.
+    /// See [`RestartIF::evaluator_coverage`].
+    pub fn restart_coverage(&self) -> Vec<(&'static str, bool, bool)> {
+        self.state
+            .restart
+            .evaluator_coverage(self.asg.assign_progress(), self.cdb.lbd_progress())
+    }
+    /// return the restart and search counters in the Prometheus text exposition format.
+    pub fn metrics_prometheus(&self) -> String {
+        let stats = self.state.stats(&self.asg, &self.cdb);
+        let (_, num_block) = self.state.restart.block_stats();
+        [
+            (
+                "splr_conflicts_total",
+                "counter",
+                "the number of conflicts",
+                stats.num_conflict as f64,
+            ),
+            (
+                "splr_decisions_total",
+                "counter",
+                "the number of decisions",
+                stats.num_decision as f64,
+            ),
+            (
+                "splr_propagations_total",
+                "counter",
+                "the number of propagations",
+                stats.num_propagation as f64,
+            ),
+            (
+                "splr_restarts_total",
+                "counter",
+                "the number of restarts",
+                stats.num_restart as f64,
+            ),
+            (
+                "splr_blocked_restarts_total",
+                "counter",
+                "the number of blocked restart checks",
+                num_block as f64,
+            ),
+            (
+                "splr_stabilizations_total",
+                "counter",
+                "the number of switches to stable (Luby) restart mode",
+                self.state.restart.stabilize_count() as f64,
+            ),
+            (
+                "splr_learnt_clauses",
+                "gauge",
+                "the number of learnt clauses",
+                stats.num_learnt as f64,
+            ),
+            (
+                "splr_unasserted_vars",
+                "gauge",
+                "the number of unasserted vars",
+                stats.num_unasserted_var as f64,
+            ),
+            (
+                "splr_lbd",
+                "gauge",
+                "the fast EMA of learnt clauses' LBD",
+                stats.lbd,
+            ),
+            (
+                "splr_lbd_trend",
+                "gauge",
+                "the trend of learnt clauses' LBD",
+                stats.lbd_trend,
+            ),
+            (
+                "splr_propagations_per_conflict",
+                "gauge",
+                "an EMA of propagations per conflict",
+                stats.propagation_per_conflict,
+            ),
+            (
+                "splr_elapsed_seconds",
+                "gauge",
+                "elapsed time",
+                stats.elapsed,
+            ),
+        ]
+        .iter()
+        .map(|(name, kind, help, val)| {
+            let val = prometheus_value(*val);
+            format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {val}\n")
+        })
+        .collect()
+    }
+    /// return the clause which implied an assigned literal `lit`,
+    /// or `None` if it is unassigned, falsified, decided, or asserted.
+    /// ```
+    /// use splr::*;
+    ///
+    /// let v: Vec<Vec<i32>> = vec![vec![1, 2], vec![-2, 3]];
+    /// let mut s = Solver::try_from((Config::default(), v.as_ref())).expect("panic");
+    /// assert_eq!(s.reason_of(1), None);
+    ///```
+    pub fn reason_of(&self, lit: i32) -> Option<Vec<i32>> {
+        if lit == 0 || self.asg.num_vars < lit.unsigned_abs() as usize {
+            return None;
+        }
+        let l = Lit::from(lit);
+        if self.asg.assigned(l) != Some(true) {
+            return None;
+        }
+        match self.asg.reason(l.vi()) {
+            AssignReason::BinaryLink(from) => Some(vec![lit, i32::from(!from)]),
+            AssignReason::Implication(cid) => {
+                Some(self.cdb[cid].iter().map(i32::from).collect::<Vec<i32>>())
+            }
+            AssignReason::Decision(_) | AssignReason::None => None,
+        }
+    }
+}
+
 /// Iterator for Solver
 /// * takes `&mut Solver`
 /// * returns `Option<Vec<i32>>`
@@ -159,7 +401,7 @@ impl<'a> Iterator for SolverIter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::assign;
+    use crate::assign::{self, PropagateIF};
 
     #[cfg_attr(not(feature = "no_IO"), test)]
     fn test_solver() {
@@ -175,6 +417,267 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reason_of() {
+        let v: Vec<Vec<i32>> = vec![vec![1, 2, 3], vec![-1, 4]];
+        let mut s = Solver::try_from((Config::default(), v.as_ref())).expect("panic");
+        s.asg.assign_by_decision(Lit::from(-2i32));
+        s.asg.assign_by_decision(Lit::from(-3i32));
+        assert!(s.asg.propagate(&mut s.cdb).is_ok());
+        assert_eq!(s.reason_of(-2), None);
+        assert_eq!(s.reason_of(2), None);
+        for l in [1, 4] {
+            let reason = s.reason_of(l).expect("no reason");
+            assert!(reason.contains(&l));
+            assert!(reason
+                .iter()
+                .filter(|k| **k != l)
+                .all(|k| s.asg.assigned(Lit::from(*k)) == Some(false)));
+        }
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_stats_channel() {
+        let (tx, rx) = std::sync::mpsc::channel::<SolverStats>();
+        let mut s = Solver::try_from(std::path::Path::new("cnfs/sample.cnf"))
+            .expect("can't load")
+            .with_stats_channel(tx);
+        s.state.stats_interval = 10;
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        drop(s);
+        let snapshots = rx.iter().collect::<Vec<SolverStats>>();
+        assert!(!snapshots.is_empty());
+        assert!(snapshots
+            .windows(2)
+            .all(|w| w[0].num_conflict < w[1].num_conflict));
+        let (tx, rx) = std::sync::mpsc::channel::<SolverStats>();
+        let mut s = Solver::try_from(std::path::Path::new("cnfs/sample.cnf"))
+            .expect("can't load")
+            .with_stats_channel(tx);
+        s.state.stats_interval = 0;
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        drop(s);
+        assert_eq!(rx.iter().count(), 0);
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_restart_controller() {
+        let history = Arc::new(Mutex::new(Vec::new()));
+        let log = history.clone();
+        let mut s = Solver::try_from(std::path::Path::new("cnfs/uf100-010.cnf"))
+            .expect("can't load")
+            .with_restart_controller(move |stats| {
+                let mut log = log.lock().unwrap();
+                let restart = (log.len() + 1) % 10 == 0;
+                log.push((stats.num_restart, restart));
+                restart
+            });
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        let history = history.lock().unwrap();
+        assert!(10 <= history.len());
+        // each `true` is followed by a restart before the next conflict.
+        assert!(history
+            .windows(2)
+            .filter(|w| w[0].1)
+            .all(|w| w[0].0 < w[1].0));
+        // the other restarts happen only at stage transitions.
+        let forced = history
+            .windows(2)
+            .filter(|w| !w[0].1 && w[0].0 < w[1].0)
+            .count();
+        assert!(forced < history.len() / 10);
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_progress() {
+        let mut s =
+            Solver::try_from(std::path::Path::new("cnfs/uf100-010.cnf")).expect("can't load");
+        let start = s.progress();
+        assert!((0.0..1.0).contains(&start));
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        let end = s.progress();
+        assert!((0.0..1.0).contains(&end));
+        assert!(start < end);
+        assert_eq!(Solver::default().progress(), 0.0);
+    }
+
+    // the assign rate is only updated with feature `assign_rate`.
+    #[cfg(feature = "assign_rate")]
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_restart_coverage() {
+        let mut s =
+            Solver::try_from(std::path::Path::new("cnfs/uf100-010.cnf")).expect("can't load");
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        let coverage = s.restart_coverage();
+        let touched = |name: &str| {
+            coverage
+                .iter()
+                .find(|(n, _, _)| *n == name)
+                .map(|(_, _, t)| *t)
+                .expect("no such evaluator")
+        };
+        assert!(touched("LBD"));
+        assert!(touched("assign"));
+        assert!(coverage
+            .iter()
+            .any(|(n, enabled, _)| *n == "LBD" && *enabled));
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_metrics_prometheus() {
+        let mut s = Solver::try_from(std::path::Path::new("cnfs/sample.cnf")).expect("can't load");
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        let text = s.metrics_prometheus();
+        let mut names = Vec::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                assert!(comment.starts_with("HELP splr_") || comment.starts_with("TYPE splr_"));
+                continue;
+            }
+            let (name, val) = line.split_once(' ').expect("no value");
+            assert!(name.chars().all(|c| c.is_ascii_lowercase() || c == '_'));
+            assert!(val.parse::<f64>().is_ok_and(f64::is_finite));
+            names.push(name);
+        }
+        for name in [
+            "splr_restarts_total",
+            "splr_blocked_restarts_total",
+            "splr_stabilizations_total",
+            "splr_lbd_trend",
+        ] {
+            assert!(names.contains(&name));
+        }
+        assert!(text.contains(&format!("splr_restarts_total {}\n", s.state[Stat::Restart])));
+        assert_eq!(prometheus_value(f64::NAN), "NaN");
+        assert_eq!(prometheus_value(f64::INFINITY), "+Inf");
+        assert_eq!(prometheus_value(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(prometheus_value(2.5), "2.5");
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_max_learnt() {
+        const CAP: usize = 30;
+        // the learnt and restart counts sent at each conflict, before `c_max_learnt`
+        // is enforced. The controller leaves only restarts at stage transitions.
+        let samples = |cap: Option<usize>| {
+            let mut config = Config::from("cnfs/uf100-010.cnf");
+            config.c_max_learnt = cap;
+            let (tx, rx) = std::sync::mpsc::channel();
+            let mut s = Solver::build(&config)
+                .expect("can't load")
+                .with_stats_channel(tx)
+                .with_restart_controller(|_| false);
+            s.state.stats_interval = 1;
+            assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+            rx.try_iter()
+                .map(|s| (s.num_learnt, s.num_restart))
+                .collect::<Vec<(usize, usize)>>()
+        };
+        assert!(CAP < samples(None).iter().map(|s| s.0).max().unwrap());
+        let counts = samples(Some(CAP));
+        // at most one learnt clause is added per conflict
+        assert!(counts.iter().all(|s| s.0 <= CAP + 1));
+        // each enforcement cuts down to the half so it can't fire at the next conflict
+        assert!(counts
+            .windows(2)
+            .all(|w| w[0].0 <= CAP || w[1].0 <= CAP / 2 + 1));
+        // and it isn't counted as a restart
+        assert!(counts.windows(2).any(|w| CAP < w[0].0));
+        assert!(counts.windows(2).all(|w| w[0].0 <= CAP || w[0].1 == w[1].1));
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_max_restart_count() {
+        let num_restart = |max_count: usize| {
+            let mut config = Config::from("cnfs/uf100-010.cnf");
+            config.rst_max_count = max_count;
+            let mut s = Solver::build(&config).expect("can't load");
+            assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+            assert_eq!(s.state.restart.restart_count(), s.state[Stat::Restart]);
+            s.state.restart.restart_count()
+        };
+        assert!(3 < num_restart(0));
+        // the end of stages and of solving don't restart past the limit
+        assert_eq!(num_restart(3), 3);
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_reset_evaluators() {
+        let mut s =
+            Solver::try_from(std::path::Path::new("cnfs/uf100-010.cnf")).expect("can't load");
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        assert!(s.cdb.lbd_progress().is_touched());
+        assert!(0 < s.state.restart.restart_count());
+        s.reset();
+        assert!(!s.cdb.lbd_progress().is_touched());
+        assert_eq!(s.state.restart.restart_count(), 0);
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_restart_audit() {
+        let mut config = Config::from("cnfs/sample.cnf");
+        config.c_seed = 7;
+        config.io_odir = std::env::temp_dir();
+        config.io_afile = std::path::PathBuf::from("splr-test-restart-audit.log");
+        let path = config.io_odir.join(&config.io_afile);
+        let mut s = Solver::build(&config).expect("can't load");
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        let log = std::fs::read_to_string(&path).expect("no audit log");
+        let _ = std::fs::remove_file(&path);
+        let mut lines = log.lines();
+        assert!(lines.next().is_some_and(|l| l.starts_with("splr ")));
+        assert_eq!(lines.next(), Some("seed 7"));
+        assert!(lines
+            .next()
+            .is_some_and(|l| l.starts_with("config Config {")));
+        assert!(lines.any(|l| l.starts_with("restart ") && l.ends_with("-> force")));
+    }
+
+    #[test]
+    fn test_solve_batch() {
+        use crate::cnf::CnfIf;
+        let sat = || CNF::from_vec_i32(vec![vec![1, 2], vec![-1, 3], vec![-2]]).unwrap();
+        let unsat =
+            || CNF::from_vec_i32(vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]]).unwrap();
+        let results = solve_batch(vec![sat(), unsat()], &Config::default());
+        assert_eq!(results.len(), 2);
+        let Ok(Certificate::SAT(ref model)) = results[0] else {
+            panic!("{:?}", results[0]);
+        };
+        assert!(model.contains(&-2) && model.contains(&1) && model.contains(&3));
+        assert_eq!(results[1], Ok(Certificate::UNSAT));
+        // more instances than threads keep their order
+        let n = 4 * std::thread::available_parallelism().map_or(1, |n| n.get()) + 1;
+        let batch = (0..n)
+            .map(|i| if i % 2 == 0 { sat() } else { unsat() })
+            .collect::<Vec<CNF>>();
+        let results = solve_batch(batch, &Config::default());
+        assert_eq!(results.len(), n);
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(i % 2 == 1, *result == Ok(Certificate::UNSAT));
+        }
+    }
+
+    #[cfg_attr(not(feature = "no_IO"), test)]
+    fn test_stats_comment_line() {
+        let mut s = Solver::try_from(std::path::Path::new("cnfs/sample.cnf")).expect("can't load");
+        assert!(matches!(s.solve(), Ok(Certificate::SAT(_))));
+        let stats = s.state.stats(&s.asg, &s.cdb);
+        let line = format!("{}", stats);
+        assert!(line.starts_with("c "));
+        assert!(line.contains(&format!("conflicts: {} ", stats.num_conflict)));
+        assert!(line.contains(&format!("restarts: {} ", stats.num_restart)));
+        assert!(0 < stats.num_conflict);
+        assert!(stats.propagation_per_conflict.is_finite());
+        assert!(1.0 <= stats.propagation_per_conflict);
+        s.state.config.io_stat_int = 10;
+        let num_conflict = stats.num_conflict - stats.num_conflict % 10;
+        assert_eq!(s.state.stats_line(&s.asg, &s.cdb, num_conflict), Some(line));
+        assert_eq!(s.state.stats_line(&s.asg, &s.cdb, num_conflict + 1), None);
+        s.state.config.quiet_mode = true;
+        assert_eq!(s.state.stats_line(&s.asg, &s.cdb, num_conflict), None);
+    }
+
     macro_rules! run {
         ($vec: expr) => {
             println!(
@@ -1,58 +1,3835 @@
 //! Module `restart` provides restart heuristics.
-use crate::types::*;
+use {
+    super::trace::Json,
+    crate::{assign::ProgressLVL, config::ConfigError, types::*},
+    std::{
+        cmp::Ordering,
+        fmt,
+        str::FromStr,
+        sync::{Arc, Mutex},
+    },
+};
 
 /// API for [`restart`](`crate::solver::RestartIF::restart`)
+///
+/// At each conflict, call `update_reuse`, `update_locality`, `add_learnt` and
+/// `update` in this order, then `set_db_size` and `set_lbd_trend` before a restart check:
+/// the reuse check must not see the new learnt, and a restart check counts
+/// conflicts by `update`. `update_conflict` applies them at once.
 pub trait RestartIF: Instantiate {
+    /// catch up with a conflict which generated a learnt clause of rank `lbd`.
+    fn update(&mut self, lbd: u16);
+    /// catch up with a conflict caused by `conflicting`, which generated `learnt`
+    /// stored as `cid` (`None` for a unit or binary learnt) of rank `lbd`,
+    /// calling the update methods in the required order.
+    fn update_conflict(
+        &mut self,
+        conflicting: Option<ClauseId>,
+        learnt: &[Lit],
+        cid: Option<ClauseId>,
+        lbd: u16,
+    ) {
+        self.update_reuse(conflicting);
+        self.update_locality(learnt);
+        if let Some(cid) = cid {
+            self.add_learnt(cid);
+        }
+        self.update(lbd);
+    }
+    /// update the number of learnt clauses in the clause DB.
+    fn set_db_size(&mut self, num_learnt: usize);
+    /// replace the trend of unassigned vars under which restarts are blocked,
+    /// by the adaptive one of `ProgressASG` (`rst_asg_adaptive`).
+    fn set_near_sol_thr(&mut self, thr: f64);
+    /// replace the LBD trend which the firing checks read, by the one of
+    /// `ProgressLBD`, which includes the average LBD under `rst_lbd_use_average`.
+    fn set_lbd_trend(&mut self, trend: f64);
+    /// record a new (non-binary) learnt clause.
+    fn add_learnt(&mut self, cid: ClauseId);
+    /// catch up with the var overlap between a new learnt and the previous one.
+    fn update_locality(&mut self, learnt: &[Lit]);
+    /// catch up with the clause which caused the current conflict.
+    fn update_reuse(&mut self, conflicting: Option<ClauseId>);
+    /// catch up with the heat of recurring conflict complexity, if `rst_rcc_thr` is set.
+    /// Callers decide what heat means; the search loop does not feed it.
+    fn update_rcc(&mut self, heat: f64);
+    /// catch up with the decision level at a conflict, if `rst_lvl_force` is set.
+    fn update_level(&mut self, lvl: DecisionLevel);
+    /// suppress restarts for the next `conflicts` conflicts, because the caller
+    /// knows the current assignment is promising.
+    fn mark_good_region(&mut self, conflicts: usize);
+    /// return the number of conflicts since the last restart.
+    ///```
+    /// use splr::{solver::{RestartIF, RestartManager}, types::*};
+    ///
+    /// let config = Config { rst_step: 8, ..Config::default() };
+    /// let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+    /// for _ in 0..5 {
+    ///     rst.update(4);
+    /// }
+    /// assert_eq!(rst.conflicts_since_restart(), 5);
+    /// assert_eq!(rst.restart_step(), 8);
+    ///```
+    fn conflicts_since_restart(&self) -> usize;
+    /// return the minimum number of conflicts between restarts, set by `rst_step`.
+    fn restart_step(&self) -> usize;
+    /// return the number of conflicts since the last change of restart mode.
+    fn conflicts_in_current_mode(&self) -> usize;
+    /// return an EMA of the number of conflicts between restarts.
+    fn conflicts_per_restart_ema(&self) -> f64;
+    /// return an EMA of the decrease of average LBD by a restart: the average over
+    /// the segment before it minus the average over the next conflicts.
+    fn restart_effectiveness(&self) -> f64;
+    /// return (the number of blocked restart checks since the last mode
+    /// switch, the total number of them).
+    fn block_stats(&self) -> (usize, usize);
+    /// return the number of switches to Luby mode, i.e. stabilizer activations.
+    fn stabilize_count(&self) -> usize;
+    /// return how close the next restart is, in [0, 1]: the ratio of conflicts
+    /// to the interval in Luby and geometric modes, the spent ratio of penetration
+    /// energy in dynamic mode, or the ratio of the last votes to `rst_vote_thr`.
+    /// It is 0 in never mode, and never NaN.
+    ///
+    /// ```
+    /// use splr::solver::{RestartIF, RestartManager, RestartMode};
+    ///
+    /// let mut rst = RestartManager::default();
+    /// rst.set_mode(RestartMode::Luby);
+    /// let mut last = rst.restart_pressure();
+    /// assert_eq!(last, 0.0);
+    /// for _ in 0..50 {
+    ///     rst.update(4);
+    ///     let pressure = rst.restart_pressure();
+    ///     assert!(last < pressure || pressure == 1.0);
+    ///     last = pressure;
+    /// }
+    /// ```
+    fn restart_pressure(&self) -> f64;
+    /// return the number of restarts since instantiation or `reset`, including
+    /// restarts at the end of stages.
+    fn restart_count(&self) -> usize;
+    /// return `true` if `rst_max_count` restarts have occurred. Then the solver
+    /// stops restarts of any kind; it still goes back to the root level at the
+    /// end of stages, which doesn't count as a restart.
+    fn is_exhausted(&self) -> bool;
+    /// return the statistics to compare runs, with the assign and LBD evaluators
+    /// passed to `restart`.
+    fn stats(&self, asg: &EmaView, lbd: &EmaView) -> RestartStats;
+    /// return every value which restart checks read, for parameter tuning.
+    /// Unlike `stats`, its fields follow the internals and may change.
+    fn full_report(&self, asg: &EmaView, lbd: &EmaView) -> RestartReport;
+    /// return (name, enabled, touched) of each evaluator which restart checks consult,
+    /// to assert the coverage of restart machinery. The assign and LBD evaluators
+    /// are owned by the caller.
+    fn evaluator_coverage(
+        &self,
+        asg: &impl ProgressEvaluatorIF,
+        lbd: &impl ProgressEvaluatorIF,
+    ) -> Vec<(&'static str, bool, bool)>;
+    /// return the trustworthiness of restart decisions in [0, 1],
+    /// based on the number of samples which the EMAs have accumulated.
+    fn confidence(&self) -> f64;
     /// check blocking and forcing restart condition.
-    fn restart(&mut self, ldb: &EmaView, ent: &EmaView) -> bool;
+    fn restart(&mut self, asg: &EmaView, ldb: &EmaView, ent: &EmaView) -> bool {
+        self.restart_reason(asg, ldb, ent).is_some()
+    }
+    /// check restart condition like `restart`, and return the cause of a restart.
+    fn restart_reason(
+        &mut self,
+        asg: &EmaView,
+        ldb: &EmaView,
+        ent: &EmaView,
+    ) -> Option<RestartReason>;
+    /// check restart condition like `restart`, and return the level to backjump to:
+    /// `Some(0)` for a full restart, or, under `rst_partial`, a level halfway to
+    /// `level` if a due restart was blocked by locality or assign trend.
+    fn restart_level(
+        &mut self,
+        asg: &EmaView,
+        ldb: &EmaView,
+        ent: &EmaView,
+        level: DecisionLevel,
+    ) -> Option<DecisionLevel>;
+    /// return a human-readable explanation of the last `restart` decision.
+    fn describe_last_decision(&self) -> String;
+    /// return (stable mode is active, the scale of the current stage,
+    /// the restart interval in Luby mode).
+    fn stabilizer_state(&self) -> (bool, usize, usize);
+    /// return the current restart policy, the cheap part of `stabilizer_state`.
+    fn mode(&self) -> RestartMode;
+    /// switch to `mode` now, e.g. by a portfolio controller.
+    /// With `rst_mode_switch` or `rst_bandit`, the next stage may switch again,
+    /// except from `Never`, which is kept until another `set_mode`.
+    fn set_mode(&mut self, mode: RestartMode);
+    /// register `f`, called with the current mode and the number of conflicts
+    /// since the previous restart at each restart. Clones share the hook.
+    fn on_restart(&mut self, f: impl FnMut(RestartMode, usize) + Send + 'static);
+    /// return the event to pass to the assignment layer after a restart,
+    /// i.e. `ResetPhase` by `rst_reset_phase`.
+    fn restart_event(&self) -> Option<SolverEvent>;
+    /// return the dynamic state, dropping parameters and thresholds.
+    fn snapshot(&self) -> RestartSnapshot;
+    /// restore the dynamic state saved by `snapshot`.
+    fn restore(&mut self, snapshot: RestartSnapshot);
+    /// clear all statistics and evaluators, keeping parameters and thresholds,
+    /// e.g. between incremental solves. The LBD evaluator belongs to the clause DB,
+    /// which resets it on `SolverEvent::Reinitialize` along with this.
+    fn reset(&mut self);
+    /// reset evaluators and internal values which became NaN or infinite,
+    /// and return their names for logging.
+    fn sanitize(&mut self) -> Vec<&'static str>;
+    /// return the number of conflicts per unit of the Luby series in stable mode.
+    fn stabilizer_scale(&self) -> f64;
+    /// set the number of conflicts per unit of the Luby series, which must be over 1.0.
+    /// It takes effect from the next Luby restart.
+    fn set_stabilizer_scale(&mut self, scale: f64) -> Result<(), ConfigError>;
     /// set stabilization parameters
     fn set_stage_parameters(&mut self, step: usize);
+    /// set the length of the current stage in learnt clauses, for `stabilization_span`.
+    fn set_stage_span(&mut self, span: usize);
+    /// return the length of the current stage in learnt clauses if it is
+    /// a stable phase, i.e. in Luby mode. A phase ends with its stage.
+    fn stabilization_span(&self) -> Option<usize>;
     /// adjust restart threshold
     fn set_segment_parameters(&mut self, segment_scale: usize);
+    /// move the parameters adjusted by `set_segment_parameters` toward their
+    /// defaults by `factor` in [0, 1], to reuse the manager for another problem.
+    fn age(&mut self, factor: f64);
+}
+
+/// restart policy
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RestartMode {
+    /// restart by penetration energy, i.e. a 'focused' mode.
+    #[default]
+    Dynamic,
+    /// restart by Luby series, i.e. a 'stable' mode.
+    Luby,
+    /// never restart, for measuring the search without restarts.
+    Never,
+    /// restart by geometric series as MiniSat, i.e. a fixed schedule.
+    Geometric,
+    /// restart by Luby series, or by penetration energy after half of a Luby interval.
+    Hybrid,
+    /// restart when a bucket filled by powered LBDs overflows (`rst_bkt_pwr`).
+    Bucket,
+}
+
+impl fmt::Display for RestartMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RestartMode::Dynamic => write!(f, "dynamic"),
+            RestartMode::Luby => write!(f, "luby"),
+            RestartMode::Never => write!(f, "never"),
+            RestartMode::Geometric => write!(f, "geometric"),
+            RestartMode::Hybrid => write!(f, "hybrid"),
+            RestartMode::Bucket => write!(f, "bucket"),
+        }
+    }
+}
+
+impl FromStr for RestartMode {
+    type Err = ConfigError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dynamic" => Ok(RestartMode::Dynamic),
+            "luby" => Ok(RestartMode::Luby),
+            "never" => Ok(RestartMode::Never),
+            "geometric" => Ok(RestartMode::Geometric),
+            "hybrid" => Ok(RestartMode::Hybrid),
+            "bucket" => Ok(RestartMode::Bucket),
+            _ => Err(ConfigError::OutOfRange("restart mode")),
+        }
+    }
 }
 
 const FUEL: f64 = 2.0;
 const SCALE: f64 = 64.0;
+/// the length of the slowest 'fast' EMA used in restart decisions (entanglement)
+const CONFIDENCE_LEN: usize = 1_000;
+/// the number of the latest learnts regarded as 'recent'
+const REUSE_WINDOW: usize = 8;
+/// the length of EMA on the rate of conflicts by recent learnts
+const REUSE_EMA_LEN: f64 = 32.0;
+/// the number of conflicts corresponding to one unit of Luby series
+const LUBY_STEP: usize = 100;
+/// the length of EMA on LBD per restart mode, used by the bandit selector
+const BANDIT_EMA_LEN: f64 = 1_000.0;
 
-/// `RestartManager` provides restart API and holds data about restart conditions.
+/// the length of EMA on the number of conflicts between restarts
+const CPR_EMA_LEN: usize = 32;
+/// the number of conflicts after a restart to compare LBD with the previous segment
+const EFFECT_WINDOW: usize = 16;
+/// the length of EMA on restart effectiveness
+const EFFECT_EMA_LEN: usize = 32;
+
+/// the factor of eagerness by a hint that the problem is UNSAT, or its inverse for SAT
+const UNSAT_HINT_EAGERNESS: f64 = 2.0;
+
+/// the clause/var ratio regarded as normal hardness by `rst_hardness`
+const HARDNESS_BASE: f64 = 4.0;
+
+const LOCALITY_EMA_LEN: usize = 16;
+const LOCALITY_EMA_SLOW: usize = 1_000;
+
+/// An EMA of Jaccard index between var sets of consecutive learnts, used for blocking restart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressLocality {
+    ema: Ema2,
+    touched: bool,
+    /// sorted vars of the previous learnt
+    last: Vec<VarId>,
+    /// a buffer reused for the next learnt
+    next: Vec<VarId>,
+}
+
+impl Default for ProgressLocality {
+    fn default() -> ProgressLocality {
+        ProgressLocality {
+            ema: Ema2::new(LOCALITY_EMA_LEN).with_slow(LOCALITY_EMA_SLOW),
+            touched: false,
+            last: Vec::new(),
+            next: Vec::new(),
+        }
+    }
+}
+
+impl EmaIF for ProgressLocality {
+    fn get_fast(&self) -> f64 {
+        self.ema.get_fast()
+    }
+    fn get_slow(&self) -> f64 {
+        self.ema.get_slow()
+    }
+    fn trend(&self) -> f64 {
+        self.ema.trend()
+    }
+}
+
+impl ProgressEvaluatorIF for ProgressLocality {
+    fn name(&self) -> &'static str {
+        "locality"
+    }
+    fn is_touched(&self) -> bool {
+        self.touched
+    }
+}
+
+impl EmaMutIF for ProgressLocality {
+    type Input = Vec<VarId>;
+    /// `vars` should be sorted.
+    fn update(&mut self, vars: Self::Input) {
+        self.next = vars;
+        self.shift();
+    }
+    fn as_view(&self) -> &EmaView {
+        self.ema.as_view()
+    }
+}
+
+impl ProgressLocality {
+    /// update with the vars of `learnt`, without allocation once buffers have grown.
+    fn update_by_lits(&mut self, learnt: &[Lit]) {
+        self.next.clear();
+        self.next.extend(learnt.iter().map(|l| l.vi()));
+        self.next.sort_unstable();
+        self.shift();
+    }
+    /// compare `next` with `last`, then make `next` the new `last`.
+    fn shift(&mut self) {
+        self.touched = true;
+        let ProgressLocality { last, next, .. } = self;
+        let (mut i, mut j, mut common) = (0, 0, 0);
+        while i < next.len() && j < last.len() {
+            match next[i].cmp(&last[j]) {
+                Ordering::Less => i += 1,
+                Ordering::Greater => j += 1,
+                Ordering::Equal => {
+                    common += 1;
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        let union = next.len() + last.len() - common;
+        if 0 < union {
+            self.ema.update(common as f64 / union as f64);
+        }
+        std::mem::swap(last, next);
+    }
+}
+
+const RCC_EMA_LEN: usize = 16;
+const RCC_EMA_SLOW: usize = 1_000;
+
+/// An EMA pair of the heat of recurring conflict complexity, given by callers,
+/// used for forcing restart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressRCC {
+    heat: Ema2,
+    /// the trend over which a restart is forced (0: disabled)
+    threshold: f64,
+    touched: bool,
+}
+
+impl Default for ProgressRCC {
+    fn default() -> ProgressRCC {
+        ProgressRCC {
+            heat: Ema2::new(RCC_EMA_LEN).with_slow(RCC_EMA_SLOW),
+            threshold: 0.0,
+            touched: false,
+        }
+    }
+}
+
+impl Instantiate for ProgressRCC {
+    fn instantiate(config: &Config, _: &CNFDescription) -> Self {
+        ProgressRCC {
+            threshold: config.rst_rcc_thr,
+            ..ProgressRCC::default()
+        }
+    }
+}
+
+impl EmaIF for ProgressRCC {
+    fn get_fast(&self) -> f64 {
+        self.heat.get_fast()
+    }
+    fn get_slow(&self) -> f64 {
+        self.heat.get_slow()
+    }
+    fn trend(&self) -> f64 {
+        // The slow EMA is 0 until a nonzero input.
+        let t = self.heat.trend();
+        if t.is_finite() {
+            t
+        } else {
+            1.0
+        }
+    }
+}
+
+impl ProgressEvaluatorIF for ProgressRCC {
+    fn name(&self) -> &'static str {
+        "RCC"
+    }
+    fn is_touched(&self) -> bool {
+        self.touched
+    }
+}
+
+impl EmaMutIF for ProgressRCC {
+    type Input = f64;
+    fn update(&mut self, heat: f64) {
+        if !self.touched {
+            // start from the first heat, as the slow EMA warms up from 0 too slowly.
+            self.touched = true;
+            self.heat = self.heat.clone().with_value(heat);
+            return;
+        }
+        self.heat.update(heat);
+    }
+    fn as_view(&self) -> &EmaView {
+        self.heat.as_view()
+    }
+}
+
+impl ProgressRCC {
+    /// return `true` if the heat is rising over the threshold.
+    pub fn is_active(&self) -> bool {
+        0.0 < self.threshold && self.threshold < self.trend()
+    }
+    /// return `true` if restarts consult it.
+    pub fn is_enabled(&self) -> bool {
+        0.0 < self.threshold
+    }
+    /// return the same evaluator with the accumulated heat cleared.
+    fn clear(&self) -> ProgressRCC {
+        ProgressRCC {
+            threshold: self.threshold,
+            ..ProgressRCC::default()
+        }
+    }
+}
+
+/// the number of restarts over which the bucket power decays, by `rst_bkt_decay`
+const BUCKET_DECAY_SPAN: f64 = 16.0;
+
+/// The curve along which the bucket power moves to 1.0 at restarts.
+/// After `n` restarts from an initial power `p`, the power is `1 + (p - 1) * d`, where `d` is:
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BucketDecay {
+    /// 1, i.e. the power is kept.
+    #[default]
+    None,
+    /// `max(0, 1 - n / 16)`, reaching 1.0 after 16 restarts.
+    Linear,
+    /// `16 / (16 + n)`, halving the distance to 1.0 after 16 restarts.
+    Inverse,
+    /// `0.5^(n / 16)`, halving the distance to 1.0 every 16 restarts.
+    Exponential,
+}
+
+impl From<usize> for BucketDecay {
+    fn from(curve: usize) -> Self {
+        match curve {
+            1 => BucketDecay::Linear,
+            2 => BucketDecay::Inverse,
+            3 => BucketDecay::Exponential,
+            _ => BucketDecay::None,
+        }
+    }
+}
+
+/// A bucket filled by LBDs raised to `power`, used for forcing restart in bucket mode.
+/// Each restart empties it and moves `power` by `decay`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressBucket {
+    /// the sum of powered LBDs since the last restart
+    sum: f64,
+    /// the exponent applied to each LBD (0: disabled)
+    power: f64,
+    /// the power given by the config
+    initial_power: f64,
+    decay: BucketDecay,
+    /// the number of restarts, i.e. shifts of the power
+    num_shift: usize,
+    /// the sum over which a restart is forced
+    threshold: f64,
+    touched: bool,
+}
+
+impl Default for ProgressBucket {
+    fn default() -> ProgressBucket {
+        ProgressBucket {
+            sum: 0.0,
+            power: 0.0,
+            initial_power: 0.0,
+            decay: BucketDecay::None,
+            num_shift: 0,
+            threshold: crate::config::RST_BKT_THR,
+            touched: false,
+        }
+    }
+}
+
+impl Instantiate for ProgressBucket {
+    fn instantiate(config: &Config, _: &CNFDescription) -> Self {
+        ProgressBucket {
+            power: config.rst_bkt_pwr,
+            initial_power: config.rst_bkt_pwr,
+            decay: BucketDecay::from(config.rst_bkt_decay),
+            threshold: config.rst_bkt_thr,
+            ..ProgressBucket::default()
+        }
+    }
+}
+
+impl EmaIF for ProgressBucket {
+    /// return the sum of powered LBDs since the last restart.
+    fn get_fast(&self) -> f64 {
+        self.sum
+    }
+    /// return how full the bucket is, which reaches 1.0 at a bucket restart.
+    fn trend(&self) -> f64 {
+        self.sum / self.threshold
+    }
+}
+
+impl ProgressEvaluatorIF for ProgressBucket {
+    fn name(&self) -> &'static str {
+        "bucket"
+    }
+    fn is_touched(&self) -> bool {
+        self.touched
+    }
+}
+
+impl ProgressBucket {
+    /// put an LBD raised to `power` into the bucket.
+    pub fn update(&mut self, lbd: u16) {
+        self.touched = true;
+        self.sum += (lbd as f64).powf(self.power);
+    }
+    /// return `true` if the bucket is full.
+    pub fn is_active(&self) -> bool {
+        self.is_enabled() && self.threshold <= self.sum
+    }
+    /// return `true` if restarts consult it.
+    pub fn is_enabled(&self) -> bool {
+        0.0 < self.power
+    }
+    /// empty the bucket and move the power along the decay curve at a restart.
+    fn shift(&mut self) {
+        self.sum = 0.0;
+        if !self.is_enabled() {
+            return;
+        }
+        self.num_shift += 1;
+        let n = self.num_shift as f64 / BUCKET_DECAY_SPAN;
+        let d = match self.decay {
+            BucketDecay::None => 1.0,
+            BucketDecay::Linear => (1.0 - n).max(0.0),
+            BucketDecay::Inverse => 1.0 / (1.0 + n),
+            BucketDecay::Exponential => 0.5_f64.powf(n),
+        };
+        self.power = 1.0 + (self.initial_power - 1.0) * d;
+    }
+    /// return the same evaluator with an empty bucket and the initial power.
+    fn clear(&self) -> ProgressBucket {
+        ProgressBucket {
+            power: self.initial_power,
+            initial_power: self.initial_power,
+            decay: self.decay,
+            threshold: self.threshold,
+            ..ProgressBucket::default()
+        }
+    }
+}
+
+/// Restart statistics returned by [`RestartIF::stats`], for comparing runs.
+/// `Display` prints a flat JSON object, and `FromStr` reads it back.
+/// Non-finite floats are printed as `null`, read back as NaN.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestartStats {
+    pub mode: RestartMode,
+    /// the total number of blocked restart checks
+    pub num_block: usize,
+    /// the number of switches to Luby mode
+    pub num_stabilize: usize,
+    /// the number of conflicts since the last restart
+    pub after_restart: usize,
+    /// the number of conflicts after a restart to the next one, in Luby mode
+    pub next_restart: Option<usize>,
+    pub asg_trend: f64,
+    pub lbd_get: f64,
+    pub lbd_trend: f64,
+}
+
+/// A snapshot of the evaluators and the stabilizer returned by
+/// [`RestartIF::full_report`], for automated parameter tuning.
+///
+/// ```
+/// use splr::{solver::{RestartIF, RestartManager, RestartMode}, Ema2, EmaMutIF};
+///
+/// let rst = RestartManager::default();
+/// let (asg, lbd) = (Ema2::new(1).with_value(1.0), Ema2::new(1).with_value(2.0));
+/// let report = rst.full_report(asg.as_view(), lbd.as_view());
+/// assert_eq!(report.mode, RestartMode::Dynamic);
+/// assert_eq!(report.asg_ema, 1.0);
+/// assert_eq!(report.asg_thr, 0.0);
+/// assert_eq!(report.lbd_fast, 2.0);
+/// assert_eq!(report.lbd_slow, 2.0);
+/// assert_eq!(report.luby_index, 0);
+/// assert_eq!(report.next_restart, 0);
+/// assert!(!report.stabilizing);
+/// assert_eq!(report.stage_scale, 1);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestartReport {
+    pub mode: RestartMode,
+    /// the fast EMA of the assign evaluator
+    pub asg_ema: f64,
+    /// the trend of unassigned vars under which restarts are blocked (0: disabled)
+    pub asg_thr: f64,
+    pub lbd_fast: f64,
+    pub lbd_slow: f64,
+    /// the number of generated values of the Luby series
+    pub luby_index: usize,
+    /// the number of conflicts after a restart to the next one in Luby mode
+    pub next_restart: usize,
+    /// `true` in Luby mode, i.e. if the stabilizer is active
+    pub stabilizing: bool,
+    /// the scale given at the last stage transition
+    pub stage_scale: usize,
+}
+
+impl fmt::Display for RestartStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let float = |x: f64| {
+            if x.is_finite() {
+                format!("{:?}", x)
+            } else {
+                "null".to_string()
+            }
+        };
+        write!(
+            f,
+            "{{\"mode\":\"{}\",\"num_block\":{},\"num_stabilize\":{},\"after_restart\":{},\"next_restart\":{},\"asg_trend\":{},\"lbd_get\":{},\"lbd_trend\":{}}}",
+            self.mode,
+            self.num_block,
+            self.num_stabilize,
+            self.after_restart,
+            self.next_restart.map_or("null".to_string(), |n| n.to_string()),
+            float(self.asg_trend),
+            float(self.lbd_get),
+            float(self.lbd_trend),
+        )
+    }
+}
+
+impl FromStr for RestartStats {
+    type Err = ConfigError;
+    /// parse a record made by `Display`. All of its keys are required.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const KEYS: [&str; 8] = [
+            "mode",
+            "num_block",
+            "num_stabilize",
+            "after_restart",
+            "next_restart",
+            "asg_trend",
+            "lbd_get",
+            "lbd_trend",
+        ];
+        let json = Json::from_str(s).map_err(|_| ConfigError::OutOfRange("restart stats"))?;
+        let Json::Object(pairs) = &json else {
+            return Err(ConfigError::OutOfRange("restart stats"));
+        };
+        if pairs.len() != KEYS.len() || pairs.iter().any(|(k, _)| !KEYS.contains(&k.as_str())) {
+            return Err(ConfigError::OutOfRange("restart stats"));
+        }
+        let int = |name| {
+            json.get(name)
+                .and_then(Json::as_usize)
+                .ok_or(ConfigError::OutOfRange(name))
+        };
+        let float = |name| match json.get(name) {
+            Some(Json::Null) => Ok(f64::NAN),
+            Some(Json::Number(x)) => Ok(*x),
+            _ => Err(ConfigError::OutOfRange(name)),
+        };
+        let Some(Json::String(mode)) = json.get("mode") else {
+            return Err(ConfigError::OutOfRange("mode"));
+        };
+        Ok(RestartStats {
+            mode: mode.parse()?,
+            num_block: int("num_block")?,
+            num_stabilize: int("num_stabilize")?,
+            after_restart: int("after_restart")?,
+            next_restart: match json.get("next_restart") {
+                Some(Json::Null) => None,
+                _ => Some(int("next_restart")?),
+            },
+            asg_trend: float("asg_trend")?,
+            lbd_get: float("lbd_get")?,
+            lbd_trend: float("lbd_trend")?,
+        })
+    }
+}
+
+/// The dynamic state of [`RestartManager`], used by [`RestartIF::snapshot`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestartSnapshot {
+    penetration_energy: f64,
+    penetration_energy_charged: f64,
+    penetration_energy_unit: f64,
+    field_scale: f64,
+    lbd_seen: bool,
+    num_learnt: usize,
+    num_samples: usize,
+    rescaled: bool,
+    jitter_wait: usize,
+    rng: u64,
+    recent_learnts: [ClauseId; REUSE_WINDOW],
+    recent_index: usize,
+    reuse_rate: f64,
+    mode: RestartMode,
+    luby: LubySeries,
+    after_restart: usize,
+    next_luby: usize,
+    geometric: GeometricSeries,
+    next_geometric: usize,
+    stage_scale: usize,
+    stage_span: usize,
+    restarts_in_mode: usize,
+    lbd_by_mode: [(f64, usize); 6],
+    locality: ProgressLocality,
+    rcc: ProgressRCC,
+    bkt: ProgressBucket,
+    lvl: ProgressLVL,
+    cpr: Ema,
+    epoch_pending: bool,
+    good_region: usize,
+    num_block: usize,
+    blocks_in_mode: usize,
+    conflicts_in_mode: usize,
+    num_stabilize: usize,
+    num_restart: usize,
+    segment_lbd: (f64, usize),
+    pre_restart_lbd: f64,
+    effect: Ema,
+    transition_pending: bool,
+}
+
+/// The cause of a restart, returned by [`RestartIF::restart_reason`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RestartReason {
+    /// a restart withheld until the next epoch boundary
+    Epoch,
+    /// var activities were rescaled (`rst_on_rescale`)
+    Rescale,
+    /// the restart mode was switched (`rst_stb_transition_restart`)
+    ModeSwitch,
+    /// recent learnts are reused frequently (`rst_reuse_thr`)
+    Reuse,
+    /// the Luby interval passed in stable mode
+    Luby,
+    /// the geometric interval passed (`rst_geometric`)
+    Geometric,
+    /// the penetration energy by LBD and entanglement ran out in dynamic mode
+    Energy,
+    /// the weighted votes of evaluators reached `rst_vote_thr` in dynamic mode
+    Vote,
+    /// the heat of recurring conflict complexity is rising (`rst_rcc_thr`)
+    RecurringConflict,
+    /// the bucket of powered LBDs is full in bucket mode
+    Bucket,
+    /// decision levels are blowing up (`rst_lvl_force`)
+    LevelBlowup,
+}
+
+/// The comparison which decided the last restart check.
 #[derive(Clone, Debug, Default)]
+struct RestartDecision {
+    evaluator: &'static str,
+    value: f64,
+    op: &'static str,
+    threshold: f64,
+    reason: Option<RestartReason>,
+}
+
+impl fmt::Display for RestartDecision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.evaluator.is_empty() {
+            return write!(f, "no restart check yet");
+        }
+        write!(
+            f,
+            "{} {:.4} {} threshold {:.4} -> {}",
+            self.evaluator,
+            self.value,
+            self.op,
+            self.threshold,
+            if self.reason.is_some() {
+                "force"
+            } else {
+                "block"
+            },
+        )
+    }
+}
+
+/// A function called at each restart. See [`RestartIF::on_restart`].
+#[allow(clippy::type_complexity)]
+#[derive(Clone)]
+pub struct RestartHook(Arc<Mutex<dyn FnMut(RestartMode, usize) + Send>>);
+
+impl fmt::Debug for RestartHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RestartHook")
+    }
+}
+
+/// `RestartManager` provides restart API and holds data about restart conditions.
+#[derive(Clone, Debug)]
 pub struct RestartManager {
     penetration_energy: f64,
     pub penetration_energy_charged: f64,
     penetration_energy_unit: f64,
     field_scale: f64,
+    /// LBD which a learnt should exceed before the first restart
+    lbd_seen_thr: u16,
+    /// `true` after a learnt with a rank over `lbd_seen_thr` was generated.
+    lbd_seen: bool,
+    /// learnt/var ratio over which restarts are suppressed
+    db_size_thr: f64,
+    num_learnt: usize,
+    num_vars: usize,
+    /// trend of unassigned vars under which restarts are suppressed
+    near_sol_thr: f64,
+    /// the LBD trend by `set_lbd_trend`, used instead of the one of the view
+    lbd_trend: Option<f64>,
+    /// the number of conflicts which have been fed to EMAs
+    num_samples: usize,
+    /// force a restart after rescaling var activities
+    on_rescale: bool,
+    /// `true` if var activities have been rescaled since the last restart
+    rescaled: bool,
+    /// the max number of conflicts to postpone a restart
+    jitter: usize,
+    /// the number of conflicts to wait before the next restart
+    jitter_wait: usize,
+    /// state of the xorshift generator for jitter
+    rng: u64,
+    /// the latest learnt clauses
+    recent_learnts: [ClauseId; REUSE_WINDOW],
+    recent_index: usize,
+    /// EMA of the rate of conflicts caused by recent learnts
+    reuse_rate: f64,
+    /// reuse rate over which a restart is forced
+    reuse_thr: f64,
+    /// the weighted sum of votes over which a restart is forced (0: disabled)
+    vote_thr: f64,
+    /// the vote weights of LBD, entanglement and reuse rate
+    vote_weights: [f64; 3],
+    /// the current restart policy
+    mode: RestartMode,
+    /// alternate policies at each stage
+    mode_switch: bool,
+    luby: LubySeries,
+    /// the number of conflicts per unit of Luby series
+    luby_step: f64,
+    /// the base of Luby series
+    luby_inc: f64,
+    /// the number of conflicts since the last restart
+    after_restart: usize,
+    /// the number of conflicts to the next restart in Luby mode
+    next_luby: usize,
+    geometric: GeometricSeries,
+    /// the number of conflicts to the next restart in geometric mode
+    next_geometric: usize,
+    /// the scale given at the last stage transition
+    stage_scale: usize,
+    /// the length of the current stage in learnt clauses, given by the caller
+    stage_span: usize,
+    /// the max number of restarts in a stable phase
+    stb_budget: usize,
+    /// the number of conflicts before the first automatic switch to Luby mode
+    stb_grace: usize,
+    /// the number of restarts since the last mode switch
+    restarts_in_mode: usize,
+    /// select the mode with the lowest LBD at each stage
+    bandit: bool,
+    /// EMA of LBD and the number of samples, per mode
+    lbd_by_mode: [(f64, usize); 6],
+    /// var overlap of consecutive learnts
+    locality: ProgressLocality,
+    /// locality over which restarts are blocked
+    locality_thr: f64,
+    /// recurring conflict complexity, which forces restarts
+    rcc: ProgressRCC,
+    /// powered LBDs since the last restart, which force restarts in bucket mode
+    bkt: ProgressBucket,
+    /// decision levels at conflicts
+    lvl: ProgressLVL,
+    /// force restarts by a rising decision level trend
+    lvl_force: bool,
+    /// use `<` rather than `<=` in threshold comparisons
+    strict: bool,
+    /// EMA of the number of conflicts between restarts
+    cpr: Ema,
+    /// the interval of sharing epochs to align restarts to
+    epoch: usize,
+    /// `true` if a restart is postponed to the next epoch boundary
+    epoch_pending: bool,
+    /// the number of conflicts under which restarts are suppressed by the caller
+    good_region: usize,
+    /// the min number of conflicts between restarts. As the search loop calls
+    /// `update` before each restart check, 0 works like 1 there.
+    step: usize,
+    /// the hard min number of conflicts between restarts of any kind
+    min_interval: usize,
+    /// the number of blocked restart checks
+    num_block: usize,
+    /// the number of blocked restart checks since the last mode switch
+    blocks_in_mode: usize,
+    /// the number of conflicts since the last mode switch
+    conflicts_in_mode: usize,
+    /// the number of switches to Luby mode
+    num_stabilize: usize,
+    /// the number of restarts
+    num_restart: usize,
+    /// the max number of restarts, after which restarts stop (0: unlimited)
+    max_count: usize,
+    /// the decisive comparison in the last restart check
+    decision: RestartDecision,
+    /// a function called at each restart
+    hook: Option<RestartHook>,
+    /// reset saved phases at each restart
+    reset_phase: bool,
+    /// the sum of LBDs and the number of conflicts since the last restart
+    segment_lbd: (f64, usize),
+    /// the average LBD over the segment before the last restart, or 0 if unknown
+    pre_restart_lbd: f64,
+    /// EMA of the decrease of average LBD by restarts
+    effect: Ema,
+    /// force a restart after a mode switch
+    transition_restart: bool,
+    /// `true` if the mode has been switched since the last restart
+    transition_pending: bool,
+    /// backjump halfway instead of blocking a due restart by progress
+    partial: bool,
+    /// `true` if the last restart check was blocked by locality or assign trend
+    progress_blocked: bool,
+}
+
+impl Default for RestartManager {
+    fn default() -> RestartManager {
+        RestartManager::instantiate(&Config::default(), &CNFDescription::default())
+    }
 }
 
 impl Instantiate for RestartManager {
-    fn instantiate(_config: &Config, _cnf: &CNFDescription) -> Self {
-        RestartManager {
-            penetration_energy: FUEL,
-            penetration_energy_charged: FUEL,
-            penetration_energy_unit: FUEL,
+    fn instantiate(config: &Config, cnf: &CNFDescription) -> Self {
+        // An eager manager forces earlier and blocks less.
+        // Refutations need exploration, while models need completing assignments.
+        let eager = config.rst_eagerness
+            * match config.c_expect_sat {
+                Some(true) => 1.0 / UNSAT_HINT_EAGERNESS,
+                Some(false) => UNSAT_HINT_EAGERNESS,
+                None => 1.0,
+            };
+        // More energy makes restarts more conservative on harder problems.
+        let hardness = if config.rst_hardness && 0 < cnf.num_of_variables {
+            let ratio = cnf.num_of_clauses as f64 / cnf.num_of_variables as f64;
+            (ratio / HARDNESS_BASE).sqrt().clamp(0.5, 2.0)
+        } else {
+            1.0
+        };
+        let fuel = FUEL * hardness / eager;
+        let mut geometric =
+            GeometricSeries::new(config.rst_geometric_step, config.rst_geometric_inc);
+        let next_geometric = geometric.next_unchecked();
+        let mut rst = RestartManager {
+            penetration_energy: fuel,
+            penetration_energy_charged: fuel,
+            penetration_energy_unit: fuel,
             field_scale: 1.0 / SCALE,
+            lbd_seen_thr: config.rst_lbd_seen_thr,
+            lbd_seen: config.rst_lbd_seen_thr == 0,
+            db_size_thr: config.rst_db_size_thr * eager,
+            num_learnt: 0,
+            num_vars: cnf.num_of_variables,
+            near_sol_thr: config.rst_near_sol_thr / eager,
+            lbd_trend: None,
+            num_samples: 0,
+            on_rescale: config.rst_on_rescale,
+            rescaled: false,
+            jitter: config.rst_jitter,
+            jitter_wait: 0,
+            rng: (config.c_seed ^ 0x9E37_79B9_7F4A_7C15).max(1),
+            recent_learnts: [ClauseId::default(); REUSE_WINDOW],
+            recent_index: 0,
+            reuse_rate: 0.0,
+            reuse_thr: config.rst_reuse_thr / eager,
+            vote_thr: config.rst_vote_thr,
+            vote_weights: [
+                config.rst_vote_lbd,
+                config.rst_vote_ent,
+                config.rst_vote_reuse,
+            ],
+            // `with_config` rejects contradictory modes; unchecked ones resolve
+            // in this order.
+            mode: if config.rst_never {
+                RestartMode::Never
+            } else if config.rst_geometric {
+                RestartMode::Geometric
+            } else if config.rst_hybrid {
+                RestartMode::Hybrid
+            } else if 0.0 < config.rst_bkt_pwr {
+                RestartMode::Bucket
+            } else {
+                RestartMode::Dynamic
+            },
+            mode_switch: config.rst_mode_switch,
+            luby: LubySeries::default(),
+            luby_step: LUBY_STEP as f64,
+            luby_inc: config.rst_luby_inc,
+            after_restart: 0,
+            next_luby: 0,
+            geometric,
+            next_geometric,
+            stage_scale: 1,
+            stage_span: 0,
+            stb_budget: config.rst_stb_budget,
+            stb_grace: config.rst_stb_grace,
+            restarts_in_mode: 0,
+            bandit: config.rst_bandit,
+            lbd_by_mode: [(0.0, 0); 6],
+            locality: ProgressLocality::default(),
+            locality_thr: config.rst_locality_thr * eager,
+            rcc: ProgressRCC::instantiate(config, cnf),
+            bkt: ProgressBucket::instantiate(config, cnf),
+            lvl: ProgressLVL::instantiate(config, cnf),
+            lvl_force: config.rst_lvl_force,
+            strict: config.rst_strict,
+            cpr: Ema::new(CPR_EMA_LEN),
+            epoch: config.rst_epoch,
+            epoch_pending: false,
+            good_region: 0,
+            step: config.rst_step,
+            min_interval: config.rst_min_interval,
+            num_block: 0,
+            blocks_in_mode: 0,
+            conflicts_in_mode: 0,
+            num_stabilize: 0,
+            num_restart: 0,
+            max_count: config.rst_max_count,
+            decision: RestartDecision::default(),
+            hook: None,
+            reset_phase: config.rst_reset_phase,
+            partial: config.rst_partial,
+            progress_blocked: false,
+            segment_lbd: (0.0, 0),
+            pre_restart_lbd: 0.0,
+            effect: Ema::new(EFFECT_EMA_LEN),
+            transition_restart: config.rst_stb_transition_restart,
+            transition_pending: false,
+        };
+        if rst.mode == RestartMode::Hybrid {
+            rst.next_luby = rst.luby_interval();
         }
+        rst
     }
     fn handle(&mut self, e: SolverEvent) {
-        if e == SolverEvent::Restart {
-            self.penetration_energy = self.penetration_energy_charged;
+        match e {
+            SolverEvent::ActivityRescale => self.rescaled = self.on_rescale,
+            SolverEvent::NewVar => self.num_vars += 1,
+            SolverEvent::Restart => {
+                if let Some(RestartHook(ref hook)) = self.hook {
+                    if let Ok(mut f) = hook.lock() {
+                        f(self.mode, self.after_restart);
+                    }
+                }
+                self.penetration_energy = self.penetration_energy_charged;
+                self.cpr.update(self.after_restart as f64);
+                let (sum, num) = self.segment_lbd;
+                self.pre_restart_lbd = if 0 < num { sum / num as f64 } else { 0.0 };
+                self.segment_lbd = (0.0, 0);
+                self.rescaled = false;
+                self.epoch_pending = false;
+                self.transition_pending = false;
+                self.reuse_rate = 0.0;
+                self.after_restart = 0;
+                self.restarts_in_mode += 1;
+                self.num_restart += 1;
+                match self.mode {
+                    RestartMode::Luby | RestartMode::Hybrid => {
+                        self.next_luby = self.luby_interval()
+                    }
+                    RestartMode::Geometric => self.next_geometric = self.geometric.next_unchecked(),
+                    _ => (),
+                }
+                self.bkt.shift();
+                if 0 < self.jitter {
+                    self.jitter_wait = (self.next_random() % (self.jitter as u64 + 1)) as usize;
+                }
+            }
+            _ => (),
         }
     }
 }
 
 impl RestartIF for RestartManager {
-    fn restart(&mut self, lbd: &EmaView, ent: &EmaView) -> bool {
-        let gscale = |x: f64| self.field_scale * (x - 1.0) + 1.0;
-        self.penetration_energy -= (lbd.trend() + gscale(ent.trend())) - 2.0;
-        self.penetration_energy < 0.0
+    #[inline]
+    fn update(&mut self, lbd: u16) {
+        self.num_samples += 1;
+        let (sum, num) = &mut self.segment_lbd;
+        *sum += lbd as f64;
+        *num += 1;
+        if *num == EFFECT_WINDOW && 0.0 < self.pre_restart_lbd {
+            self.effect
+                .update(self.pre_restart_lbd - *sum / EFFECT_WINDOW as f64);
+        }
+        self.after_restart += 1;
+        self.conflicts_in_mode += 1;
+        self.good_region = self.good_region.saturating_sub(1);
+        let (ema, num) = &mut self.lbd_by_mode[self.mode as usize];
+        if *num == 0 {
+            *ema = lbd as f64;
+        } else {
+            *ema += (lbd as f64 - *ema) / BANDIT_EMA_LEN;
+        }
+        *num += 1;
+        if self.lbd_seen_thr < lbd {
+            self.lbd_seen = true;
+        }
+        if self.bkt.is_enabled() {
+            self.bkt.update(lbd);
+        }
+    }
+    fn set_db_size(&mut self, num_learnt: usize) {
+        self.num_learnt = num_learnt;
+    }
+    fn set_near_sol_thr(&mut self, thr: f64) {
+        self.near_sol_thr = thr;
+    }
+    fn set_lbd_trend(&mut self, trend: f64) {
+        self.lbd_trend = Some(trend);
+    }
+    fn add_learnt(&mut self, cid: ClauseId) {
+        self.recent_learnts[self.recent_index] = cid;
+        self.recent_index = (self.recent_index + 1) % REUSE_WINDOW;
+    }
+    fn update_locality(&mut self, learnt: &[Lit]) {
+        if self.locality_thr == 0.0 {
+            return;
+        }
+        self.locality.update_by_lits(learnt);
+    }
+    fn update_rcc(&mut self, heat: f64) {
+        if self.rcc.is_enabled() {
+            self.rcc.update(heat);
+        }
+    }
+    fn update_level(&mut self, lvl: DecisionLevel) {
+        if self.lvl_force {
+            self.lvl.update(lvl);
+        }
+    }
+    fn update_reuse(&mut self, conflicting: Option<ClauseId>) {
+        let reused = conflicting.is_some_and(|cid| self.recent_learnts.contains(&cid));
+        self.reuse_rate += (reused as usize as f64 - self.reuse_rate) / REUSE_EMA_LEN;
+    }
+    fn mark_good_region(&mut self, conflicts: usize) {
+        self.good_region = conflicts;
+    }
+    fn conflicts_since_restart(&self) -> usize {
+        self.after_restart
+    }
+    fn restart_step(&self) -> usize {
+        self.step
+    }
+    fn conflicts_in_current_mode(&self) -> usize {
+        self.conflicts_in_mode
+    }
+    fn conflicts_per_restart_ema(&self) -> f64 {
+        self.cpr.get()
+    }
+    fn restart_effectiveness(&self) -> f64 {
+        self.effect.get()
+    }
+    fn block_stats(&self) -> (usize, usize) {
+        (self.blocks_in_mode, self.num_block)
+    }
+    fn stabilize_count(&self) -> usize {
+        self.num_stabilize
+    }
+    fn restart_count(&self) -> usize {
+        self.num_restart
+    }
+    fn is_exhausted(&self) -> bool {
+        0 < self.max_count && self.max_count <= self.num_restart
+    }
+    fn restart_pressure(&self) -> f64 {
+        let ratio = |a: f64, b: f64| if 0.0 < b { a / b } else { 1.0 };
+        let p = match self.mode {
+            RestartMode::Never => 0.0,
+            RestartMode::Luby | RestartMode::Hybrid => {
+                ratio(self.after_restart as f64, self.next_luby as f64)
+            }
+            RestartMode::Geometric => ratio(self.after_restart as f64, self.next_geometric as f64),
+            RestartMode::Bucket => ratio(self.bkt.sum, self.bkt.threshold),
+            RestartMode::Dynamic if 0.0 < self.vote_thr => {
+                if self.decision.evaluator == "weighted votes" {
+                    ratio(self.decision.value, self.vote_thr)
+                } else {
+                    0.0
+                }
+            }
+            RestartMode::Dynamic => {
+                1.0 - ratio(self.penetration_energy, self.penetration_energy_charged)
+            }
+        };
+        if p.is_nan() {
+            0.0
+        } else {
+            p.clamp(0.0, 1.0)
+        }
+    }
+    fn stats(&self, asg: &EmaView, lbd: &EmaView) -> RestartStats {
+        RestartStats {
+            mode: self.mode,
+            num_block: self.num_block,
+            num_stabilize: self.num_stabilize,
+            after_restart: self.after_restart,
+            next_restart: match self.mode {
+                RestartMode::Luby | RestartMode::Hybrid => Some(self.next_luby),
+                RestartMode::Geometric => Some(self.next_geometric),
+                _ => None,
+            },
+            asg_trend: asg.trend(),
+            lbd_get: lbd.get_fast(),
+            lbd_trend: lbd.trend(),
+        }
+    }
+    fn full_report(&self, asg: &EmaView, lbd: &EmaView) -> RestartReport {
+        let (stabilizing, stage_scale, next_restart) = self.stabilizer_state();
+        RestartReport {
+            mode: self.mode,
+            asg_ema: asg.get_fast(),
+            asg_thr: self.near_sol_thr,
+            lbd_fast: lbd.get_fast(),
+            lbd_slow: lbd.get_slow(),
+            luby_index: self.luby.index(),
+            next_restart,
+            stabilizing,
+            stage_scale,
+        }
+    }
+    fn evaluator_coverage(
+        &self,
+        asg: &impl ProgressEvaluatorIF,
+        lbd: &impl ProgressEvaluatorIF,
+    ) -> Vec<(&'static str, bool, bool)> {
+        vec![
+            (asg.name(), 0.0 < self.near_sol_thr, asg.is_touched()),
+            (
+                lbd.name(),
+                !matches!(
+                    self.mode,
+                    RestartMode::Never | RestartMode::Geometric | RestartMode::Bucket
+                ),
+                lbd.is_touched(),
+            ),
+            (
+                self.locality.name(),
+                0.0 < self.locality_thr,
+                self.locality.is_touched(),
+            ),
+            (
+                self.rcc.name(),
+                self.rcc.is_enabled(),
+                self.rcc.is_touched(),
+            ),
+            (
+                self.bkt.name(),
+                self.bkt.is_enabled(),
+                self.bkt.is_touched(),
+            ),
+            (self.lvl.name(), self.lvl_force, self.lvl.is_touched()),
+        ]
+    }
+    fn confidence(&self) -> f64 {
+        let n = self.num_samples.min(i32::MAX as usize) as i32;
+        1.0 - (1.0 - 1.0 / CONFIDENCE_LEN as f64).powi(n)
+    }
+    fn restart_reason(
+        &mut self,
+        asg: &EmaView,
+        lbd: &EmaView,
+        ent: &EmaView,
+    ) -> Option<RestartReason> {
+        if self.is_exhausted() {
+            self.progress_blocked = false;
+            self.decide(
+                "restart budget",
+                self.num_restart as f64,
+                ">=",
+                self.max_count as f64,
+                None,
+            );
+            return None;
+        }
+        let ready = self.restart_condition(asg, lbd, ent);
+        if self.epoch != 0 {
+            self.epoch_pending |= ready;
+            if !self.epoch_pending {
+                return None;
+            }
+            let fire = self.num_samples.is_multiple_of(self.epoch);
+            self.decide(
+                "conflicts mod epoch",
+                (self.num_samples % self.epoch) as f64,
+                if fire { "=" } else { ">" },
+                0.0,
+                fire.then_some(RestartReason::Epoch),
+            );
+        }
+        if self.decision.reason.is_some() && self.after_restart < self.min_interval {
+            self.block(
+                "min interval",
+                self.after_restart as f64,
+                "<",
+                self.min_interval as f64,
+            );
+        }
+        self.decision.reason
+    }
+    fn restart_level(
+        &mut self,
+        asg: &EmaView,
+        lbd: &EmaView,
+        ent: &EmaView,
+        level: DecisionLevel,
+    ) -> Option<DecisionLevel> {
+        if self.restart(asg, lbd, ent) {
+            return Some(0);
+        }
+        // The blocking condition skipped the firing check, so run it now.
+        if !self.partial
+            || self.is_exhausted()
+            || !self.progress_blocked
+            || self.after_restart < self.min_interval
+            || !self.fires(lbd, ent)
+        {
+            return None;
+        }
+        self.penetration_energy = self.penetration_energy_charged;
+        self.after_restart = 0;
+        Some(level / 2)
+    }
+    fn describe_last_decision(&self) -> String {
+        self.decision.to_string()
+    }
+    fn stabilizer_state(&self) -> (bool, usize, usize) {
+        (
+            self.mode == RestartMode::Luby,
+            self.stage_scale,
+            self.next_luby,
+        )
+    }
+    fn mode(&self) -> RestartMode {
+        self.mode
+    }
+    fn set_mode(&mut self, mode: RestartMode) {
+        self.switch_mode(mode);
+    }
+    fn on_restart(&mut self, f: impl FnMut(RestartMode, usize) + Send + 'static) {
+        self.hook = Some(RestartHook(Arc::new(Mutex::new(f))));
+    }
+    fn restart_event(&self) -> Option<SolverEvent> {
+        self.reset_phase.then_some(SolverEvent::ResetPhase)
+    }
+    fn reset(&mut self) {
+        *self = self.clone_fresh();
+    }
+    fn sanitize(&mut self) -> Vec<&'static str> {
+        let mut broken = Vec::new();
+        if !self.locality.get_fast().is_finite() || !self.locality.get_slow().is_finite() {
+            self.locality = ProgressLocality::default();
+            broken.push(self.locality.name());
+        }
+        if !self.rcc.get_fast().is_finite() || !self.rcc.get_slow().is_finite() {
+            self.rcc = self.rcc.clear();
+            broken.push(self.rcc.name());
+        }
+        if !self.bkt.get().is_finite() {
+            self.bkt = self.bkt.clear();
+            broken.push(self.bkt.name());
+        }
+        if !self.lvl.get().is_finite() || !self.lvl.trend().is_finite() {
+            self.lvl = self.lvl.clear();
+            broken.push(self.lvl.name());
+        }
+        if !self.cpr.get().is_finite() {
+            self.cpr = Ema::new(CPR_EMA_LEN);
+            broken.push("conflicts per restart");
+        }
+        if !self.effect.get().is_finite() || !self.pre_restart_lbd.is_finite() {
+            self.effect = Ema::new(EFFECT_EMA_LEN);
+            self.pre_restart_lbd = 0.0;
+            broken.push("restart effectiveness");
+        }
+        if !self.segment_lbd.0.is_finite() || self.lbd_by_mode.iter().any(|e| !e.0.is_finite()) {
+            self.segment_lbd = (0.0, 0);
+            self.lbd_by_mode = [(0.0, 0); 6];
+            broken.push("LBD by mode");
+        }
+        if !self.reuse_rate.is_finite() {
+            self.reuse_rate = 0.0;
+            broken.push("reuse rate");
+        }
+        if ![
+            self.penetration_energy,
+            self.penetration_energy_charged,
+            self.penetration_energy_unit,
+        ]
+        .iter()
+        .all(|e| e.is_finite())
+        {
+            if !self.penetration_energy_unit.is_finite() {
+                self.penetration_energy_unit = FUEL;
+            }
+            if !self.penetration_energy_charged.is_finite() {
+                self.penetration_energy_charged =
+                    self.penetration_energy_unit * self.stage_scale as f64;
+            }
+            self.penetration_energy = self.penetration_energy_charged;
+            broken.push("penetration energy");
+        }
+        broken
+    }
+    fn snapshot(&self) -> RestartSnapshot {
+        RestartSnapshot {
+            penetration_energy: self.penetration_energy,
+            penetration_energy_charged: self.penetration_energy_charged,
+            penetration_energy_unit: self.penetration_energy_unit,
+            field_scale: self.field_scale,
+            lbd_seen: self.lbd_seen,
+            num_learnt: self.num_learnt,
+            num_samples: self.num_samples,
+            rescaled: self.rescaled,
+            jitter_wait: self.jitter_wait,
+            rng: self.rng,
+            recent_learnts: self.recent_learnts,
+            recent_index: self.recent_index,
+            reuse_rate: self.reuse_rate,
+            mode: self.mode,
+            luby: self.luby.clone(),
+            after_restart: self.after_restart,
+            next_luby: self.next_luby,
+            geometric: self.geometric.clone(),
+            next_geometric: self.next_geometric,
+            stage_scale: self.stage_scale,
+            stage_span: self.stage_span,
+            restarts_in_mode: self.restarts_in_mode,
+            lbd_by_mode: self.lbd_by_mode,
+            locality: self.locality.clone(),
+            rcc: self.rcc.clone(),
+            bkt: self.bkt.clone(),
+            lvl: self.lvl.clone(),
+            cpr: self.cpr.clone(),
+            epoch_pending: self.epoch_pending,
+            good_region: self.good_region,
+            num_block: self.num_block,
+            blocks_in_mode: self.blocks_in_mode,
+            conflicts_in_mode: self.conflicts_in_mode,
+            num_stabilize: self.num_stabilize,
+            num_restart: self.num_restart,
+            segment_lbd: self.segment_lbd,
+            pre_restart_lbd: self.pre_restart_lbd,
+            effect: self.effect.clone(),
+            transition_pending: self.transition_pending,
+        }
+    }
+    fn restore(&mut self, snapshot: RestartSnapshot) {
+        self.penetration_energy = snapshot.penetration_energy;
+        self.penetration_energy_charged = snapshot.penetration_energy_charged;
+        self.penetration_energy_unit = snapshot.penetration_energy_unit;
+        self.field_scale = snapshot.field_scale;
+        self.lbd_seen = snapshot.lbd_seen;
+        self.num_learnt = snapshot.num_learnt;
+        self.num_samples = snapshot.num_samples;
+        self.rescaled = snapshot.rescaled;
+        self.jitter_wait = snapshot.jitter_wait;
+        self.rng = snapshot.rng;
+        self.recent_learnts = snapshot.recent_learnts;
+        self.recent_index = snapshot.recent_index;
+        self.reuse_rate = snapshot.reuse_rate;
+        self.mode = snapshot.mode;
+        self.luby = snapshot.luby;
+        self.after_restart = snapshot.after_restart;
+        self.next_luby = snapshot.next_luby;
+        self.geometric = snapshot.geometric;
+        self.next_geometric = snapshot.next_geometric;
+        self.stage_scale = snapshot.stage_scale;
+        self.stage_span = snapshot.stage_span;
+        self.restarts_in_mode = snapshot.restarts_in_mode;
+        self.lbd_by_mode = snapshot.lbd_by_mode;
+        self.locality = snapshot.locality;
+        self.rcc = snapshot.rcc;
+        self.bkt = snapshot.bkt;
+        self.lvl = snapshot.lvl;
+        self.cpr = snapshot.cpr;
+        self.epoch_pending = snapshot.epoch_pending;
+        self.good_region = snapshot.good_region;
+        self.num_block = snapshot.num_block;
+        self.blocks_in_mode = snapshot.blocks_in_mode;
+        self.conflicts_in_mode = snapshot.conflicts_in_mode;
+        self.num_stabilize = snapshot.num_stabilize;
+        self.num_restart = snapshot.num_restart;
+        self.segment_lbd = snapshot.segment_lbd;
+        self.pre_restart_lbd = snapshot.pre_restart_lbd;
+        self.effect = snapshot.effect;
+        self.transition_pending = snapshot.transition_pending;
     }
     fn set_segment_parameters(&mut self, segment_scale: usize) {
         let factor = 0.5 * (segment_scale.trailing_zeros() + 1) as f64;
         self.field_scale = 1.0 / (SCALE - factor);
         self.penetration_energy_unit *= 10.0_f64.powf(-0.1);
     }
+    fn stabilizer_scale(&self) -> f64 {
+        self.luby_step
+    }
+    fn set_stabilizer_scale(&mut self, scale: f64) -> Result<(), ConfigError> {
+        if scale.is_nan() || scale <= 1.0 {
+            return Err(ConfigError::OutOfRange("stabilizer scale"));
+        }
+        self.luby_step = scale;
+        Ok(())
+    }
+    fn age(&mut self, factor: f64) {
+        let factor = factor.clamp(0.0, 1.0);
+        self.penetration_energy_unit += (FUEL - self.penetration_energy_unit) * factor;
+        self.field_scale += (1.0 / SCALE - self.field_scale) * factor;
+    }
+    fn set_stage_span(&mut self, span: usize) {
+        self.stage_span = span;
+    }
+    fn stabilization_span(&self) -> Option<usize> {
+        (self.mode == RestartMode::Luby).then_some(self.stage_span)
+    }
     fn set_stage_parameters(&mut self, stage_scale: usize) {
+        self.stage_scale = stage_scale;
         let e = self.penetration_energy_unit * (stage_scale as f64);
         self.penetration_energy_charged = e;
         self.penetration_energy = e;
+        if matches!(
+            self.mode,
+            RestartMode::Never | RestartMode::Geometric | RestartMode::Hybrid | RestartMode::Bucket
+        ) {
+            return;
+        }
+        // Keep a pure dynamic phase until the grace period passes.
+        if self.num_samples < self.stb_grace {
+            return;
+        }
+        if self.bandit {
+            let mode = self.select_mode();
+            self.switch_mode(mode);
+        } else if self.mode_switch {
+            self.switch_mode(match self.mode {
+                RestartMode::Dynamic => RestartMode::Luby,
+                RestartMode::Luby => RestartMode::Dynamic,
+                mode => mode,
+            });
+        }
+    }
+}
+
+impl RestartManager {
+    /// check blocking and forcing restart condition, regardless of epochs.
+    fn restart_condition(&mut self, asg: &EmaView, lbd: &EmaView, ent: &EmaView) -> bool {
+        self.progress_blocked = false;
+        if self.mode == RestartMode::Never {
+            return self.decide("never mode", 1.0, "=", 1.0, None);
+        }
+        if 0 < self.good_region {
+            return self.block("good region", self.good_region as f64, ">", 0.0);
+        }
+        if self.after_restart < self.step {
+            return self.block(
+                "conflicts after restart",
+                self.after_restart as f64,
+                "<",
+                self.step as f64,
+            );
+        }
+        if self.rescaled {
+            return self.decide(
+                "activity rescale",
+                1.0,
+                "=",
+                1.0,
+                Some(RestartReason::Rescale),
+            );
+        }
+        if self.transition_pending {
+            return self.decide(
+                "mode switch",
+                1.0,
+                "=",
+                1.0,
+                Some(RestartReason::ModeSwitch),
+            );
+        }
+        if self.rcc.is_active() {
+            return self.decide(
+                self.rcc.name(),
+                self.rcc.trend(),
+                ">",
+                self.rcc.threshold,
+                Some(RestartReason::RecurringConflict),
+            );
+        }
+        if self.lvl_force && self.lvl.is_active() {
+            return self.decide(
+                self.lvl.name(),
+                self.lvl.trend(),
+                ">",
+                self.lvl.threshold(),
+                Some(RestartReason::LevelBlowup),
+            );
+        }
+        // Under voting, the reuse rate is one of the votes.
+        if 0.0 < self.reuse_thr
+            && self.vote_thr == 0.0
+            && self.below(self.reuse_thr, self.reuse_rate)
+        {
+            return self.decide(
+                "reuse rate",
+                self.reuse_rate,
+                ">",
+                self.reuse_thr,
+                Some(RestartReason::Reuse),
+            );
+        }
+        if 0 < self.jitter_wait {
+            self.jitter_wait -= 1;
+            return self.block("jitter wait", self.jitter_wait as f64 + 1.0, ">", 0.0);
+        }
+        if !self.lbd_seen {
+            return self.block(
+                "max LBD",
+                self.lbd_seen_thr as f64,
+                "<=",
+                self.lbd_seen_thr as f64,
+            );
+        }
+        let db_limit = self.db_size_thr * (self.num_vars as f64);
+        if 0.0 < self.db_size_thr && self.below(db_limit, self.num_learnt as f64) {
+            return self.block("learnt DB size", self.num_learnt as f64, ">", db_limit);
+        }
+        // Highly local conflicts suggest progress in a subproblem.
+        if 0.0 < self.locality_thr && self.below(self.locality_thr, self.locality.get()) {
+            self.progress_blocked = true;
+            return self.block(
+                self.locality.name(),
+                self.locality.get(),
+                ">",
+                self.locality_thr,
+            );
+        }
+        // A shrinking number of unassigned vars means we are close to a model.
+        if 0.0 < self.near_sol_thr && self.below(asg.trend(), self.near_sol_thr) {
+            self.progress_blocked = true;
+            return self.block("assign trend", asg.trend(), "<", self.near_sol_thr);
+        }
+        if self.mode == RestartMode::Luby
+            && 0 < self.stb_budget
+            && self.stb_budget <= self.restarts_in_mode
+        {
+            return self.block(
+                "stable restarts",
+                self.restarts_in_mode as f64,
+                ">=",
+                self.stb_budget as f64,
+            );
+        }
+        self.fires(lbd, ent)
+    }
+    /// check the firing condition of the current mode.
+    fn fires(&mut self, lbd: &EmaView, ent: &EmaView) -> bool {
+        if self.mode == RestartMode::Bucket {
+            let fire = self.bkt.is_active();
+            return self.decide(
+                self.bkt.name(),
+                self.bkt.get(),
+                if fire { ">=" } else { "<" },
+                self.bkt.threshold,
+                fire.then_some(RestartReason::Bucket),
+            );
+        }
+        if self.mode == RestartMode::Geometric {
+            let fire = self.next_geometric <= self.after_restart;
+            return self.decide(
+                "conflicts after restart",
+                self.after_restart as f64,
+                if fire { ">=" } else { "<" },
+                self.next_geometric as f64,
+                fire.then_some(RestartReason::Geometric),
+            );
+        }
+        if self.mode == RestartMode::Hybrid {
+            if self.next_luby <= self.after_restart {
+                return self.decide(
+                    "conflicts after restart",
+                    self.after_restart as f64,
+                    ">=",
+                    self.next_luby as f64,
+                    Some(RestartReason::Luby),
+                );
+            }
+            // The dynamic signal may restart early, but not in the first half.
+            if 2 * self.after_restart <= self.next_luby {
+                return self.decide(
+                    "conflicts after restart",
+                    self.after_restart as f64,
+                    "<=",
+                    self.next_luby as f64 / 2.0,
+                    None,
+                );
+            }
+        }
+        if self.mode == RestartMode::Luby {
+            let fire = self.next_luby <= self.after_restart;
+            return self.decide(
+                "conflicts after restart",
+                self.after_restart as f64,
+                if fire { ">=" } else { "<" },
+                self.next_luby as f64,
+                fire.then_some(RestartReason::Luby),
+            );
+        }
+        let lbd_trend = self.lbd_trend.unwrap_or_else(|| lbd.trend());
+        if 0.0 < self.vote_thr {
+            let [w_lbd, w_ent, w_reuse] = self.vote_weights;
+            let votes = [
+                (w_lbd, 1.0 < lbd_trend),
+                (w_ent, 1.0 < ent.trend()),
+                (
+                    w_reuse,
+                    0.0 < self.reuse_thr && self.reuse_thr < self.reuse_rate,
+                ),
+            ];
+            let sum = votes
+                .iter()
+                .filter(|(_, v)| *v)
+                .map(|(w, _)| w)
+                .sum::<f64>();
+            let fire = self.below(self.vote_thr, sum);
+            return self.decide(
+                "weighted votes",
+                sum,
+                if fire { ">=" } else { "<" },
+                self.vote_thr,
+                fire.then_some(RestartReason::Vote),
+            );
+        }
+        let gscale = |x: f64| self.field_scale * (x - 1.0) + 1.0;
+        self.penetration_energy -= (lbd_trend + gscale(ent.trend())) - 2.0;
+        let fire = self.below(self.penetration_energy, 0.0);
+        self.decide(
+            "penetration energy",
+            self.penetration_energy,
+            if fire { "<" } else { ">=" },
+            0.0,
+            fire.then_some(RestartReason::Energy),
+        )
+    }
+    /// record the decisive comparison of a restart check, and return `restart`.
+    fn decide(
+        &mut self,
+        evaluator: &'static str,
+        value: f64,
+        op: &'static str,
+        threshold: f64,
+        reason: Option<RestartReason>,
+    ) -> bool {
+        self.decision = RestartDecision {
+            evaluator,
+            value,
+            op,
+            threshold,
+            reason,
+        };
+        reason.is_some()
+    }
+    /// count and record a blocked restart check, and return `false`.
+    fn block(
+        &mut self,
+        evaluator: &'static str,
+        value: f64,
+        op: &'static str,
+        threshold: f64,
+    ) -> bool {
+        self.num_block += 1;
+        self.blocks_in_mode += 1;
+        self.decide(evaluator, value, op, threshold, None)
+    }
+    /// compare with a threshold in the configured strictness.
+    fn below(&self, a: f64, b: f64) -> bool {
+        if self.strict {
+            a < b
+        } else {
+            a <= b
+        }
+    }
+    fn switch_mode(&mut self, mode: RestartMode) {
+        if self.mode == mode {
+            return;
+        }
+        match mode {
+            RestartMode::Luby => {
+                self.next_luby = self.luby_interval();
+                self.num_stabilize += 1;
+            }
+            RestartMode::Geometric => self.next_geometric = self.geometric.next_unchecked(),
+            RestartMode::Hybrid => self.next_luby = self.luby_interval(),
+            _ => (),
+        }
+        self.restarts_in_mode = 0;
+        self.blocks_in_mode = 0;
+        self.conflicts_in_mode = 0;
+        self.transition_pending = self.transition_restart;
+        self.mode = mode;
+    }
+    /// return an untried mode, or the mode with the lowest LBD.
+    fn select_mode(&self) -> RestartMode {
+        let [dyn_stat, luby_stat, ..] = self.lbd_by_mode;
+        if dyn_stat.1 == 0 {
+            RestartMode::Dynamic
+        } else if luby_stat.1 == 0 || luby_stat.0 < dyn_stat.0 {
+            RestartMode::Luby
+        } else {
+            RestartMode::Dynamic
+        }
+    }
+    /// build a `RestartManager` after rejecting invalid or contradictory options.
+    pub fn with_config(config: &Config, cnf: &CNFDescription) -> Result<Self, ConfigError> {
+        config.check_restart_options()?;
+        Ok(RestartManager::instantiate(config, cnf))
+    }
+    /// return the first `n` restart points, in conflicts, in Luby mode.
+    /// Restarts in dynamic mode depend on LBD and can't be previewed.
+    pub fn schedule_preview(&self, n: usize) -> Vec<usize> {
+        let mut luby = self.luby.clone();
+        let mut point = 0;
+        (0..n)
+            .map(|_| {
+                point += (self.luby_step * luby.next_with_base(self.luby_inc)) as usize;
+                point
+            })
+            .collect::<Vec<usize>>()
+    }
+    /// return the number of conflicts to the next restart in Luby mode.
+    fn luby_interval(&mut self) -> usize {
+        (self.luby_step * self.luby.next_with_base(self.luby_inc)) as usize
+    }
+    /// return a short description of the restart schedule for logging.
+    pub fn schedule_description(&self) -> String {
+        if self.mode == RestartMode::Geometric {
+            let mut geometric = self.geometric.clone();
+            geometric.reset();
+            format!(
+                "geometric restarts at intervals {:?}...",
+                (0..10)
+                    .map(|_| geometric.next_unchecked())
+                    .collect::<Vec<usize>>()
+            )
+        } else if self.mode == RestartMode::Bucket {
+            format!(
+                "bucket restarts by LBD^{} summed up to {}",
+                self.bkt.power, self.bkt.threshold
+            )
+        } else if self.mode == RestartMode::Hybrid {
+            format!(
+                "Luby restarts at {:?}..., or dynamic ones after half an interval",
+                self.schedule_preview(10)
+            )
+        } else if self.mode_switch {
+            format!(
+                "dynamic restarts, alternated with Luby restarts at {:?}...",
+                self.schedule_preview(10)
+            )
+        } else {
+            "dynamic restarts by LBD and entanglement".to_string()
+        }
+    }
+    /// return a copy which keeps parameters and thresholds, but no statistics.
+    pub fn clone_fresh(&self) -> Self {
+        let mut geometric = self.geometric.clone();
+        geometric.reset();
+        let next_geometric = geometric.next_unchecked();
+        let mut rst = RestartManager {
+            penetration_energy: self.penetration_energy_charged,
+            lbd_seen: self.lbd_seen_thr == 0,
+            num_learnt: 0,
+            lbd_trend: None,
+            num_samples: 0,
+            rescaled: false,
+            recent_learnts: [ClauseId::default(); REUSE_WINDOW],
+            recent_index: 0,
+            reuse_rate: 0.0,
+            jitter_wait: 0,
+            mode: match self.mode {
+                RestartMode::Never
+                | RestartMode::Geometric
+                | RestartMode::Hybrid
+                | RestartMode::Bucket => self.mode,
+                _ => RestartMode::Dynamic,
+            },
+            luby: LubySeries::default(),
+            after_restart: 0,
+            next_luby: 0,
+            geometric,
+            next_geometric,
+            restarts_in_mode: 0,
+            lbd_by_mode: [(0.0, 0); 6],
+            locality: ProgressLocality::default(),
+            rcc: self.rcc.clear(),
+            bkt: self.bkt.clear(),
+            lvl: self.lvl.clear(),
+            cpr: Ema::new(CPR_EMA_LEN),
+            epoch_pending: false,
+            good_region: 0,
+            num_block: 0,
+            blocks_in_mode: 0,
+            conflicts_in_mode: 0,
+            num_stabilize: 0,
+            num_restart: 0,
+            decision: RestartDecision::default(),
+            progress_blocked: false,
+            segment_lbd: (0.0, 0),
+            pre_restart_lbd: 0.0,
+            effect: Ema::new(EFFECT_EMA_LEN),
+            transition_pending: false,
+            ..self.clone()
+        };
+        if rst.mode == RestartMode::Hybrid {
+            rst.next_luby = rst.luby_interval();
+        }
+        rst
+    }
+    /// xorshift64
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hot_ema() -> Ema2 {
+        let mut e = Ema2::new(1).with_slow(1000).with_value(1.0);
+        e.update(100.0);
+        e
+    }
+
+    fn cold_ema() -> Ema2 {
+        let mut e = Ema2::new(1).with_slow(1000).with_value(1.0);
+        e.update(0.0);
+        e
+    }
+
+    #[test]
+    fn test_lbd_seen_threshold() {
+        let config = Config {
+            rst_lbd_seen_thr: 8,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let (asg, lbd, ent) = (
+            Ema2::new(1).with_value(1.0),
+            hot_ema(),
+            Ema2::new(1).with_value(1.0),
+        );
+        for _ in 0..100 {
+            rst.update(8);
+            assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        }
+        rst.update(9);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_db_size_suppression() {
+        let config = Config {
+            rst_db_size_thr: 1.0,
+            ..Config::default()
+        };
+        let cnf = CNFDescription {
+            num_of_variables: 100,
+            ..CNFDescription::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &cnf);
+        let (asg, lbd, ent) = (
+            Ema2::new(1).with_value(1.0),
+            hot_ema(),
+            Ema2::new(1).with_value(1.0),
+        );
+        rst.set_db_size(500);
+        assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        rst.set_db_size(50);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_near_solution_suppression() {
+        let config = Config {
+            rst_near_sol_thr: 0.5,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let (lbd, ent) = (hot_ema(), Ema2::new(1).with_value(1.0));
+        let asg = cold_ema();
+        assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        let asg = Ema2::new(1).with_value(1.0);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_confidence() {
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        assert_eq!(rst.confidence(), 0.0);
+        let mut last = 0.0;
+        for _ in 0..10 {
+            for _ in 0..500 {
+                rst.update(4);
+            }
+            assert!(last < rst.confidence());
+            last = rst.confidence();
+        }
+        assert!(0.99 < last && last <= 1.0);
+    }
+
+    #[test]
+    fn test_restart_on_rescale() {
+        let cold = Ema2::new(1).with_value(1.0);
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        rst.handle(SolverEvent::ActivityRescale);
+        assert!(!rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+        let config = Config {
+            rst_on_rescale: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert!(!rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+        rst.handle(SolverEvent::ActivityRescale);
+        assert!(rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+        rst.handle(SolverEvent::Restart);
+        assert!(!rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+    }
+
+    #[test]
+    fn test_restart_jitter() {
+        let restarts = |seed: u64| -> Vec<usize> {
+            let config = Config {
+                c_seed: seed,
+                rst_jitter: 16,
+                ..Config::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+            let (asg, lbd, ent) = (
+                Ema2::new(1).with_value(1.0),
+                hot_ema(),
+                Ema2::new(1).with_value(1.0),
+            );
+            (0..1000)
+                .filter(|_| {
+                    let go = rst.restart(asg.as_view(), lbd.as_view(), ent.as_view());
+                    if go {
+                        rst.handle(SolverEvent::Restart);
+                    }
+                    go
+                })
+                .collect::<Vec<usize>>()
+        };
+        assert_eq!(restarts(1), restarts(1));
+        assert_ne!(restarts(1), restarts(2));
+    }
+
+    #[test]
+    fn test_clone_fresh() {
+        let config = Config {
+            rst_lbd_seen_thr: 8,
+            rst_db_size_thr: 2.0,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        for _ in 0..100 {
+            rst.update(10);
+        }
+        rst.set_db_size(1000);
+        let fresh = rst.clone_fresh();
+        assert_eq!(fresh.lbd_seen_thr, 8);
+        assert_eq!(fresh.db_size_thr, 2.0);
+        assert_eq!(
+            fresh.penetration_energy_charged,
+            rst.penetration_energy_charged
+        );
+        assert!(rst.lbd_seen && !fresh.lbd_seen);
+        assert_eq!(fresh.num_samples, 0);
+        assert_eq!(fresh.num_learnt, 0);
+        assert_eq!(fresh.confidence(), 0.0);
+    }
+
+    #[test]
+    fn test_with_config() {
+        let cnf = CNFDescription::default();
+        assert!(RestartManager::with_config(&Config::default(), &cnf).is_ok());
+        let config = Config {
+            rst_db_size_thr: -1.0,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::OutOfRange("rst_db_size_thr"))
+        );
+        let config = Config {
+            rst_near_sol_thr: -1.0,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::OutOfRange("rst_near_sol_thr"))
+        );
+        let config = Config {
+            rst_near_sol_thr: 0.5,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).is_ok(),
+            cfg!(feature = "assign_rate")
+        );
+        let config = Config {
+            rst_never: true,
+            rst_geometric: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_never", "rst_geometric"))
+        );
+        let config = Config {
+            rst_never: true,
+            rst_hybrid: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_never", "rst_hybrid"))
+        );
+        let config = Config {
+            rst_never: true,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_never", "rst_mode_switch"))
+        );
+        let config = Config {
+            rst_never: true,
+            rst_bandit: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_never", "rst_bandit"))
+        );
+        let config = Config {
+            rst_geometric: true,
+            rst_hybrid: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_geometric", "rst_hybrid"))
+        );
+        let config = Config {
+            rst_bandit: true,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_bandit", "rst_mode_switch"))
+        );
+        let config = Config {
+            rst_geometric: true,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_geometric", "rst_mode_switch"))
+        );
+        let config = Config {
+            rst_geometric: true,
+            rst_bandit: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_geometric", "rst_bandit"))
+        );
+        let config = Config {
+            rst_hybrid: true,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_hybrid", "rst_mode_switch"))
+        );
+        let config = Config {
+            rst_hybrid: true,
+            rst_bandit: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_hybrid", "rst_bandit"))
+        );
+        let config = Config {
+            rst_bkt_pwr: 1.0,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_bkt_pwr", "rst_mode_switch"))
+        );
+        // local search runs only at restarts by heuristics
+        let config = Config {
+            rst_never: true,
+            rst_local_search: 100,
+            ..Config::default()
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &cnf).err(),
+            Some(ConfigError::Conflict("rst_never", "rst_local_search"))
+        );
+    }
+
+    #[test]
+    fn test_reuse_spike() {
+        let cold = Ema2::new(1).with_value(1.0);
+        let config = Config {
+            rst_reuse_thr: 0.5,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        for i in 1..=REUSE_WINDOW {
+            rst.add_learnt(ClauseId::from(i));
+        }
+        for _ in 0..100 {
+            rst.update_reuse(Some(ClauseId::from(1000)));
+            rst.update_reuse(None);
+        }
+        assert!(!rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+        for _ in 0..100 {
+            rst.update_reuse(Some(ClauseId::from(REUSE_WINDOW)));
+        }
+        assert!(rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+        rst.handle(SolverEvent::Restart);
+        assert!(!rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+    }
+
+    #[test]
+    fn test_mode_switch() {
+        let (asg, lbd, ent) = (
+            Ema2::new(1).with_value(1.0),
+            hot_ema(),
+            Ema2::new(1).with_value(1.0),
+        );
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.mode, RestartMode::Dynamic);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        rst.handle(SolverEvent::Restart);
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode, RestartMode::Luby);
+        // Luby mode ignores the hot LBD signal until the Luby interval passes.
+        let interval = rst.next_luby;
+        for _ in 1..interval {
+            rst.update(4);
+            assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        }
+        rst.update(4);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        rst.handle(SolverEvent::Restart);
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode, RestartMode::Dynamic);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_stabilizer_state() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.stabilizer_state(), (false, 1, 0));
+        let mut luby = LubySeries::default();
+        for (n, scale) in [1, 2, 1, 1, 2, 4].iter().enumerate() {
+            rst.set_stage_parameters(*scale);
+            let (active, s, interval) = rst.stabilizer_state();
+            assert_eq!(active, n % 2 == 0);
+            assert_eq!(active, rst.mode() == RestartMode::Luby);
+            assert_eq!(s, *scale);
+            if active {
+                assert_eq!(interval, LUBY_STEP * luby.next_unchecked());
+            }
+        }
+    }
+
+    #[test]
+    fn test_stable_phase_budget() {
+        let cold = Ema2::new(1).with_value(1.0);
+        let config = Config {
+            rst_mode_switch: true,
+            rst_stb_budget: 3,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        rst.set_stage_parameters(1);
+        assert!(rst.stabilizer_state().0);
+        let mut num_restart = 0;
+        for _ in 0..100_000 {
+            rst.update(4);
+            if rst.restart(cold.as_view(), cold.as_view(), cold.as_view()) {
+                rst.handle(SolverEvent::Restart);
+                num_restart += 1;
+            }
+        }
+        assert_eq!(num_restart, 3);
+        // the budget is refilled at the next stable phase.
+        rst.set_stage_parameters(1);
+        rst.set_stage_parameters(1);
+        for _ in 0..1_000 {
+            rst.update(4);
+        }
+        assert!(rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+    }
+
+    #[test]
+    fn test_weighted_voting() {
+        let config = Config {
+            rst_vote_thr: 1.5,
+            rst_vote_lbd: 1.0,
+            rst_vote_ent: 1.0,
+            ..Config::default()
+        };
+        let cold = Ema2::new(1).with_value(1.0);
+        let hot = hot_ema();
+        let check = |lbd: &Ema2, ent: &Ema2| {
+            let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+            rst.update(4);
+            rst.restart_reason(cold.as_view(), lbd.as_view(), ent.as_view())
+        };
+        assert_eq!(check(&cold, &cold), None);
+        assert_eq!(check(&hot, &cold), None);
+        assert_eq!(check(&cold, &hot), None);
+        assert_eq!(check(&hot, &hot), Some(RestartReason::Vote));
+        // Without voting, a hot LBD alone drains penetration energy.
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        rst.update(4);
+        assert!(rst.restart(cold.as_view(), hot.as_view(), cold.as_view()));
+        assert!(RestartManager::with_config(
+            &Config {
+                rst_vote_ent: -1.0,
+                ..Config::default()
+            },
+            &CNFDescription::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_set_mode() {
+        let cold = Ema2::new(1).with_value(1.0);
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        assert_eq!(rst.mode(), RestartMode::Dynamic);
+        rst.set_mode(RestartMode::Luby);
+        assert_eq!(rst.mode(), RestartMode::Luby);
+        assert!(rst.stabilizer_state().0);
+        assert_eq!(rst.stabilize_count(), 1);
+        rst.set_mode(RestartMode::Never);
+        for _ in 0..10_000 {
+            rst.update(4);
+            assert!(!rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+        }
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode(), RestartMode::Never);
+        rst.set_mode(RestartMode::Dynamic);
+        assert_eq!(rst.mode(), RestartMode::Dynamic);
+        assert!(rst.restart(cold.as_view(), hot_ema().as_view(), cold.as_view()));
+    }
+
+    #[test]
+    fn test_stabilizer_grace() {
+        let config = Config {
+            rst_mode_switch: true,
+            rst_stb_grace: 500,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        for _ in 0..4 {
+            for _ in 0..100 {
+                rst.update(4);
+            }
+            rst.set_stage_parameters(1);
+            assert_eq!(rst.mode(), RestartMode::Dynamic);
+        }
+        for _ in 0..100 {
+            rst.update(4);
+        }
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode(), RestartMode::Luby);
+        assert_eq!(rst.stabilize_count(), 1);
+    }
+
+    #[test]
+    fn test_stabilize_count() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.stabilize_count(), 0);
+        for i in 1..=10 {
+            rst.set_stage_parameters(1);
+            assert_eq!(rst.mode() == RestartMode::Luby, i % 2 == 1);
+        }
+        assert_eq!(rst.stabilize_count(), 5);
+        // switching to the current mode is not an activation.
+        rst.switch_mode(RestartMode::Dynamic);
+        assert_eq!(rst.stabilize_count(), 5);
+        assert_eq!(rst.clone_fresh().stabilize_count(), 0);
+    }
+
+    #[test]
+    fn test_max_count() {
+        let config = Config {
+            rst_max_count: 3,
+            rst_partial: true,
+            ..Config::default()
+        };
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let mut num_restart = 0;
+        for _ in 0..1_000 {
+            rst.update(4);
+            if rst
+                .restart_level(asg.as_view(), lbd.as_view(), ent.as_view(), 10)
+                .is_some()
+            {
+                rst.handle(SolverEvent::Restart);
+                num_restart += 1;
+            }
+        }
+        assert_eq!(num_restart, 3);
+        assert_eq!(rst.restart_count(), 3);
+        assert_eq!(
+            rst.describe_last_decision(),
+            "restart budget 3.0000 >= threshold 3.0000 -> block"
+        );
+        let mut fresh = rst.clone_fresh();
+        assert_eq!(fresh.restart_count(), 0);
+        fresh.update(4);
+        assert!(fresh.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_stabilization_span() {
+        use crate::solver::stage::StageManager;
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.stabilization_span(), None);
+        let mut stm = StageManager::new(10);
+        let mut now = 0;
+        let mut spans = Vec::new();
+        for _ in 0..8 {
+            stm.prepare_new_stage(10, now);
+            now += stm.current_span();
+            rst.set_stage_span(stm.current_span());
+            rst.set_stage_parameters(stm.current_scale());
+            match rst.mode() {
+                RestartMode::Luby => {
+                    assert_eq!(rst.stabilization_span(), Some(stm.current_span()));
+                    spans.push(stm.current_span());
+                }
+                _ => assert_eq!(rst.stabilization_span(), None),
+            }
+        }
+        // A stage grows by the unit size at each cycle, not by a factor.
+        assert_eq!(spans, [10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn test_relax() {
+        use crate::cdb::ProgressLBD;
+        let retriggered = |relax: f64| {
+            let config = Config {
+                rst_relax: relax,
+                ..Config::default()
+            };
+            let cnf = CNFDescription::default();
+            let (asg, ent) = (Ema2::new(1).with_value(1.0), Ema2::new(1).with_value(1.0));
+            let mut rst = RestartManager::instantiate(&config, &cnf);
+            let mut lbd = ProgressLBD::instantiate(&config, &cnf);
+            for _ in 0..40_000 {
+                lbd.update(4);
+            }
+            for _ in 0..8 {
+                lbd.update(40);
+                rst.update(40);
+            }
+            assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+            rst.handle(SolverEvent::Restart);
+            lbd.shift();
+            lbd.update(4);
+            rst.update(4);
+            rst.restart(asg.as_view(), lbd.as_view(), ent.as_view())
+        };
+        assert!(retriggered(1.0));
+        assert!(!retriggered(0.25));
+    }
+
+    #[test]
+    fn test_lbd_use_average() {
+        use crate::cdb::ProgressLBD;
+        let num_restart = |use_average: bool| {
+            let config = Config {
+                rst_lbd_use_average: use_average,
+                ..Config::default()
+            };
+            let cnf = CNFDescription::default();
+            let (asg, ent) = (Ema2::new(1).with_value(1.0), Ema2::new(1).with_value(1.0));
+            let mut rst = RestartManager::instantiate(&config, &cnf);
+            let mut lbd = ProgressLBD::instantiate(&config, &cnf);
+            let mut num_restart = 0;
+            // LBDs rise for good after a long low phase
+            for d in [2, 8] {
+                for _ in 0..20_000 {
+                    lbd.update(d);
+                    rst.update(d);
+                    rst.set_lbd_trend(lbd.trend());
+                    if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                        rst.handle(SolverEvent::Restart);
+                        num_restart += 1;
+                    }
+                }
+            }
+            num_restart
+        };
+        // the average LBD keeps the trend high long after the slow EMA caught up.
+        assert!(num_restart(false) < num_restart(true));
+    }
+
+    #[test]
+    fn test_restart_pressure() {
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        assert_eq!(rst.restart_pressure(), 0.0);
+        rst.update(4);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        assert_eq!(rst.restart_pressure(), 1.0);
+        rst.penetration_energy = f64::NAN;
+        assert_eq!(rst.restart_pressure(), 0.0);
+        rst.set_mode(RestartMode::Luby);
+        rst.next_luby = 0;
+        assert_eq!(rst.restart_pressure(), 1.0);
+        rst.set_mode(RestartMode::Never);
+        assert_eq!(rst.restart_pressure(), 0.0);
+    }
+
+    #[test]
+    fn test_blocking_in_luby_mode() {
+        let config = Config {
+            rst_mode_switch: true,
+            rst_near_sol_thr: 0.5,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode(), RestartMode::Luby);
+        let cold = Ema2::new(1).with_value(1.0);
+        for _ in 0..1_000 {
+            rst.update(4);
+        }
+        // a Luby restart is due, but a shrinking number of unassigned vars blocks it.
+        let active = cold_ema();
+        assert!(!rst.restart(active.as_view(), cold.as_view(), cold.as_view()));
+        assert_eq!(rst.block_stats().0, 1);
+        assert!(rst.restart(cold.as_view(), cold.as_view(), cold.as_view()));
+    }
+
+    #[test]
+    fn test_schedule_preview() {
+        let rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        let mut luby = LubySeries::default();
+        let mut point = 0;
+        let expected = (0..10)
+            .map(|_| {
+                point += LUBY_STEP * luby.next_unchecked();
+                point
+            })
+            .collect::<Vec<usize>>();
+        assert_eq!(rst.schedule_preview(10), expected);
+        assert_eq!(&rst.schedule_preview(4), &[100, 300, 400, 500]);
+        let config = Config {
+            rst_luby_inc: 1.5,
+            ..Config::default()
+        };
+        let rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(&rst.schedule_preview(6), &[100, 250, 350, 450, 600, 825]);
+    }
+
+    #[test]
+    fn test_bandit_selection() {
+        let config = Config {
+            rst_bandit: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        // Dynamic mode yields LBD 10; then Luby mode is tried.
+        for _ in 0..100 {
+            rst.update(10);
+        }
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode, RestartMode::Luby);
+        // Luby mode yields LBD 4, so it keeps being selected.
+        for _ in 0..10 {
+            for _ in 0..100 {
+                rst.update(4);
+            }
+            rst.set_stage_parameters(1);
+            assert_eq!(rst.mode, RestartMode::Luby);
+        }
+        // Luby mode gets worse than dynamic mode.
+        for _ in 0..10_000 {
+            rst.update(20);
+        }
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode, RestartMode::Dynamic);
+    }
+
+    #[test]
+    fn test_recurring_conflict_complexity() {
+        let cold = Ema2::new(1).with_value(1.0);
+        let reason = |rcc_thr: f64| {
+            let config = Config {
+                rst_rcc_thr: rcc_thr,
+                ..Config::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+            for _ in 0..2_000 {
+                rst.update(4);
+                rst.update_rcc(1.0);
+                assert_eq!(
+                    rst.restart_reason(cold.as_view(), cold.as_view(), cold.as_view()),
+                    None
+                );
+            }
+            for _ in 0..20 {
+                rst.update(4);
+                rst.update_rcc(10.0);
+            }
+            (
+                rst.rcc.is_touched(),
+                rst.restart_reason(cold.as_view(), cold.as_view(), cold.as_view()),
+            )
+        };
+        assert_eq!(reason(1.5), (true, Some(RestartReason::RecurringConflict)));
+        assert_eq!(reason(0.0), (false, None));
+        assert!(RestartManager::with_config(
+            &Config {
+                rst_rcc_thr: -1.0,
+                ..Config::default()
+            },
+            &CNFDescription::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_restart_stats() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let asg = Ema2::new(1).with_value(1.0);
+        let lbd = hot_ema();
+        for _ in 0..10 {
+            rst.update(4);
+        }
+        let stats = rst.stats(asg.as_view(), lbd.as_view());
+        assert_eq!(stats.mode, RestartMode::Dynamic);
+        assert_eq!(stats.after_restart, 10);
+        assert_eq!(stats.next_restart, None);
+        assert_eq!(stats.to_string().parse::<RestartStats>(), Ok(stats));
+        rst.set_stage_parameters(1);
+        let stats = rst.stats(asg.as_view(), lbd.as_view());
+        assert!(stats.next_restart.is_some());
+        let json = stats.to_string();
+        assert!(json.starts_with("{\"mode\":\"luby\",\"num_block\":0,\"num_stabilize\":1,"));
+        assert_eq!(json.parse::<RestartStats>(), Ok(stats));
+        // a fresh EMA has a neutral trend.
+        let pristine = Ema2::new(1);
+        assert_eq!(rst.stats(pristine.as_view(), lbd.as_view()).asg_trend, 1.0);
+        // NaN is not JSON.
+        let json = RestartStats {
+            asg_trend: f64::NAN,
+            ..rst.stats(asg.as_view(), lbd.as_view())
+        }
+        .to_string();
+        assert!(json.contains("\"asg_trend\":null"));
+        assert!(json.parse::<RestartStats>().unwrap().asg_trend.is_nan());
+        assert!("{\"mode\":\"luby\",\"num_block\":x}"
+            .parse::<RestartStats>()
+            .is_err());
+        // a partial record
+        let full = rst.stats(asg.as_view(), lbd.as_view()).to_string();
+        let partial = format!("{}}}", &full[..full.rfind(",\"lbd_trend\"").unwrap()]);
+        assert_eq!(
+            partial.parse::<RestartStats>(),
+            Err(ConfigError::OutOfRange("restart stats"))
+        );
+        // a duplicated key with a missing one
+        let json = full.replace("\"num_block\"", "\"num_stabilize\"");
+        assert!(json.parse::<RestartStats>().is_err());
+    }
+
+    #[test]
+    fn test_update_conflict() {
+        let config = Config {
+            rst_locality_thr: 0.5,
+            rst_reuse_thr: 0.5,
+            ..Config::default()
+        };
+        let mut atomic = RestartManager::instantiate(&config, &CNFDescription::default());
+        let mut ordered = atomic.clone();
+        let mut reordered = atomic.clone();
+        for i in 2..300_usize {
+            let learnt = (0..3 + i % 4)
+                .map(|j| Lit::from(((i + j) % 40 + 1) as i32))
+                .collect::<Vec<Lit>>();
+            // every other conflict is caused by the learnt of this conflict
+            let cid = (i % 3 != 0).then(|| ClauseId::from(i));
+            let conflicting = Some(ClauseId::from(if i % 2 == 0 { i } else { i - 1 }));
+            let lbd = (i % 7) as u16;
+            atomic.update_conflict(conflicting, &learnt, cid, lbd);
+            ordered.update_reuse(conflicting);
+            ordered.update_locality(&learnt);
+            if let Some(cid) = cid {
+                ordered.add_learnt(cid);
+            }
+            ordered.update(lbd);
+            if let Some(cid) = cid {
+                reordered.add_learnt(cid);
+            }
+            reordered.update(lbd);
+            reordered.update_locality(&learnt);
+            reordered.update_reuse(conflicting);
+        }
+        assert_eq!(atomic.snapshot(), ordered.snapshot());
+        assert_ne!(atomic.snapshot(), reordered.snapshot());
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let (asg, lbd, ent) = (
+            Ema2::new(1).with_value(1.0),
+            hot_ema(),
+            Ema2::new(1).with_value(1.0),
+        );
+        let config = Config {
+            rst_mode_switch: true,
+            rst_jitter: 8,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let drive = |rst: &mut RestartManager| {
+            for i in 1..200 {
+                rst.update(i % 7);
+                rst.add_learnt(ClauseId::from(i as usize));
+                if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                    rst.handle(SolverEvent::Restart);
+                }
+            }
+            rst.set_stage_parameters(2);
+        };
+        drive(&mut rst);
+        let saved = rst.snapshot();
+        let state = (rst.stabilizer_state(), rst.confidence());
+        drive(&mut rst);
+        assert_ne!(rst.snapshot(), saved);
+        rst.restore(saved.clone());
+        assert_eq!(rst.snapshot(), saved);
+        assert_eq!((rst.stabilizer_state(), rst.confidence()), state);
+    }
+
+    #[test]
+    fn test_locality_blocking() {
+        let (asg, lbd, ent) = (
+            Ema2::new(1).with_value(1.0),
+            hot_ema(),
+            Ema2::new(1).with_value(1.0),
+        );
+        let config = Config {
+            rst_locality_thr: 0.5,
+            ..Config::default()
+        };
+        let learnt = |vars: &[i32]| vars.iter().map(|v| Lit::from(*v)).collect::<Vec<Lit>>();
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        for i in 0..100 {
+            rst.update_locality(&learnt(&[4 * i + 1, 4 * i + 2, -(4 * i + 3)]));
+        }
+        assert!(rst.locality.get() < 0.1);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        for _ in 0..100 {
+            rst.update_locality(&learnt(&[1, -2, 3]));
+        }
+        assert!(0.9 < rst.locality.get());
+        assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_comparison_strictness() {
+        let (asg, lbd, ent) = (
+            Ema2::new(1).with_value(1.0),
+            hot_ema(),
+            Ema2::new(1).with_value(1.0),
+        );
+        let cnf = CNFDescription {
+            num_of_variables: 100,
+            ..CNFDescription::default()
+        };
+        // #learnts is just on the threshold.
+        for (strict, restart) in [(true, true), (false, false)] {
+            let config = Config {
+                rst_db_size_thr: 1.0,
+                rst_strict: strict,
+                ..Config::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &cnf);
+            rst.set_db_size(100);
+            assert_eq!(
+                rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()),
+                restart
+            );
+        }
+    }
+
+    #[test]
+    fn test_conflicts_per_restart_ema() {
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        for i in 0..1_000 {
+            for _ in 0..[10, 30][i % 2] {
+                rst.update(4);
+            }
+            rst.handle(SolverEvent::Restart);
+        }
+        assert!((rst.conflicts_per_restart_ema() - 20.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_epoch_alignment() {
+        let config = Config {
+            rst_epoch: 50,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let mut restarts = Vec::new();
+        for i in 1..=500 {
+            rst.update(4);
+            if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                rst.handle(SolverEvent::Restart);
+                restarts.push(i);
+            }
+        }
+        assert_eq!(restarts, (1..=10).map(|k| 50 * k).collect::<Vec<usize>>());
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        rst.update(4);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_block_stats_per_mode() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let check = |rst: &mut RestartManager, n: usize| {
+            rst.mark_good_region(n);
+            for _ in 0..n {
+                assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+                rst.update(4);
+            }
+        };
+        check(&mut rst, 5);
+        assert_eq!(rst.block_stats(), (5, 5));
+        rst.set_stage_parameters(1);
+        assert!(rst.stabilizer_state().0);
+        assert_eq!(rst.block_stats(), (0, 5));
+        check(&mut rst, 3);
+        assert_eq!(rst.block_stats(), (3, 8));
+        rst.set_stage_parameters(2);
+        assert_eq!(rst.block_stats(), (0, 8));
+    }
+
+    #[test]
+    fn test_reset() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), cold_ema());
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        rst.set_stage_parameters(1);
+        for _ in 0..1_000 {
+            rst.update(4);
+            if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                rst.handle(SolverEvent::Restart);
+            }
+        }
+        rst.update(4);
+        assert!(rst.stabilizer_state().0);
+        assert_ne!(rst.conflicts_since_restart(), 0);
+        assert_ne!(rst.conflicts_per_restart_ema(), 0.0);
+        rst.reset();
+        assert!(!rst.stabilizer_state().0);
+        assert_eq!(rst.mode(), RestartMode::Dynamic);
+        assert_eq!(rst.conflicts_since_restart(), 0);
+        assert_eq!(rst.block_stats(), (0, 0));
+        assert_eq!(rst.conflicts_per_restart_ema(), 0.0);
+        assert_eq!(rst.describe_last_decision(), "no restart check yet");
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), cold_ema());
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        assert!(rst.sanitize().is_empty());
+        rst.penetration_energy = f64::NAN;
+        rst.cpr.update(f64::INFINITY);
+        for _ in 0..100 {
+            rst.update(4);
+            assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        }
+        assert_eq!(
+            rst.sanitize(),
+            vec!["conflicts per restart", "penetration energy"]
+        );
+        assert!(rst.sanitize().is_empty());
+        assert!(rst.conflicts_per_restart_ema().is_finite());
+        rst.update(4);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    #[test]
+    fn test_never_mode() {
+        let config = Config {
+            rst_never: true,
+            rst_mode_switch: true,
+            rst_on_rescale: true,
+            ..Config::default()
+        };
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.mode, RestartMode::Never);
+        for i in 0..10_000 {
+            rst.update(1 + (i % 12) as u16);
+            if i % 1_000 == 0 {
+                rst.set_stage_parameters(1 + i / 1_000);
+                rst.handle(SolverEvent::ActivityRescale);
+            }
+            assert_eq!(
+                rst.restart_level(asg.as_view(), lbd.as_view(), ent.as_view(), 10),
+                None
+            );
+        }
+        assert_eq!(rst.mode, RestartMode::Never);
+        assert_eq!(rst.clone_fresh().mode, RestartMode::Never);
+        assert_eq!(
+            rst.describe_last_decision(),
+            "never mode 1.0000 = threshold 1.0000 -> block"
+        );
+    }
+
+    #[test]
+    fn test_geometric_mode() {
+        let config = Config {
+            rst_geometric: true,
+            rst_geometric_inc: 1.5,
+            rst_geometric_step: 100,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.mode(), RestartMode::Geometric);
+        let mut intervals = Vec::new();
+        while intervals.len() < 6 {
+            rst.update(4);
+            if let Some(reason) = rst.restart_reason(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                assert_eq!(reason, RestartReason::Geometric);
+                intervals.push(rst.after_restart);
+                rst.handle(SolverEvent::Restart);
+                rst.set_stage_parameters(1 + intervals.len());
+            }
+        }
+        assert_eq!(intervals, [100, 150, 225, 337, 506, 759]);
+        assert_eq!(rst.mode(), RestartMode::Geometric);
+        let fresh = rst.clone_fresh();
+        assert_eq!(fresh.mode(), RestartMode::Geometric);
+        assert_eq!(fresh.next_geometric, 100);
+        // `rst_mode_switch` has no effect on `instantiate` but `with_config` rejects it.
+        let config = Config {
+            rst_geometric_inc: 0.5,
+            rst_mode_switch: false,
+            ..config
+        };
+        assert_eq!(
+            RestartManager::with_config(&config, &CNFDescription::default()).err(),
+            Some(ConfigError::OutOfRange("rst_geometric_inc"))
+        );
+    }
+
+    #[test]
+    fn test_hybrid_mode() {
+        let config = Config {
+            rst_hybrid: true,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let (asg, ent) = (Ema2::new(1).with_value(1.0), Ema2::new(1).with_value(1.0));
+        let (hot, cold) = (hot_ema(), Ema2::new(1).with_value(1.0));
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.mode(), RestartMode::Hybrid);
+        let interval = rst.next_luby;
+        assert_eq!(interval, LUBY_STEP);
+        // a high LBD can't restart in the first half of the interval
+        let mut reasons = Vec::new();
+        while reasons.is_empty() {
+            rst.update(4);
+            if let Some(r) = rst.restart_reason(asg.as_view(), hot.as_view(), ent.as_view()) {
+                reasons.push((r, rst.after_restart));
+            }
+        }
+        assert_eq!(reasons, [(RestartReason::Energy, interval / 2 + 1)]);
+        rst.handle(SolverEvent::Restart);
+        rst.set_stage_parameters(2);
+        assert_eq!(rst.mode(), RestartMode::Hybrid);
+        // without a high LBD, Luby restarts at the full interval
+        let interval = rst.next_luby;
+        let reasons = (0..interval)
+            .map(|_| {
+                rst.update(4);
+                rst.restart_reason(asg.as_view(), cold.as_view(), ent.as_view())
+            })
+            .collect::<Vec<_>>();
+        assert!(reasons[..reasons.len() - 1].iter().all(|r| r.is_none()));
+        assert_eq!(reasons.last(), Some(&Some(RestartReason::Luby)));
+        assert_eq!(rst.clone_fresh().mode(), RestartMode::Hybrid);
+        assert_eq!(rst.clone_fresh().next_luby, LUBY_STEP);
+    }
+
+    #[test]
+    fn test_bucket_mode() {
+        let config = Config {
+            rst_bkt_pwr: 2.0,
+            rst_bkt_thr: 100.0,
+            ..Config::default()
+        };
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.mode(), RestartMode::Bucket);
+        // each LBD of 3 puts 9 into the bucket, which overflows at the 12th conflict.
+        for _ in 0..2 {
+            let reasons = (0..12)
+                .map(|_| {
+                    rst.update(3);
+                    rst.restart_reason(asg.as_view(), lbd.as_view(), ent.as_view())
+                })
+                .collect::<Vec<_>>();
+            assert!(reasons[..11].iter().all(|r| r.is_none()));
+            assert_eq!(reasons[11], Some(RestartReason::Bucket));
+            rst.handle(SolverEvent::Restart);
+            rst.set_stage_parameters(2);
+            assert_eq!(rst.mode(), RestartMode::Bucket);
+        }
+        let fresh = rst.clone_fresh();
+        assert_eq!(fresh.mode(), RestartMode::Bucket);
+        assert_eq!(fresh.bkt.threshold, 100.0);
+        assert_eq!(fresh.bkt.get(), 0.0);
+        assert_eq!(
+            RestartManager::with_config(
+                &Config {
+                    rst_bkt_thr: 0.0,
+                    ..config
+                },
+                &CNFDescription::default()
+            )
+            .err(),
+            Some(ConfigError::OutOfRange("rst_bkt_thr"))
+        );
+    }
+
+    #[test]
+    fn test_bucket_trend() {
+        let config = Config {
+            rst_bkt_pwr: 1.0,
+            rst_bkt_thr: 20.0,
+            ..Config::default()
+        };
+        let mut bkt = ProgressBucket::instantiate(&config, &CNFDescription::default());
+        assert_eq!(bkt.trend(), 0.0);
+        let mut last = 0.0;
+        for _ in 0..5 {
+            bkt.update(4);
+            assert!(last < bkt.trend());
+            last = bkt.trend();
+        }
+        assert_eq!((bkt.get(), bkt.trend()), (20.0, 1.0));
+        assert!(bkt.is_active());
+        bkt.shift();
+        assert_eq!(bkt.trend(), 0.0);
+    }
+
+    #[test]
+    fn test_bucket_decay() {
+        let powers = |curve: usize| {
+            let config = Config {
+                rst_bkt_pwr: 3.0,
+                rst_bkt_decay: curve,
+                ..Config::default()
+            };
+            let mut bkt = ProgressBucket::instantiate(&config, &CNFDescription::default());
+            (0..=32)
+                .map(|_| {
+                    let p = bkt.power;
+                    bkt.shift();
+                    p
+                })
+                .collect::<Vec<f64>>()
+        };
+        let at = |v: &[f64]| [v[0], v[8], v[16], v[32]];
+        assert_eq!(at(&powers(0)), [3.0, 3.0, 3.0, 3.0]);
+        assert_eq!(at(&powers(1)), [3.0, 2.0, 1.0, 1.0]);
+        let inverse = at(&powers(2));
+        let exponential = at(&powers(3));
+        for (p, q) in inverse
+            .iter()
+            .zip([3.0, 1.0 + 4.0 / 3.0, 2.0, 1.0 + 2.0 / 3.0])
+        {
+            assert!((p - q).abs() < 1e-9);
+        }
+        for (p, q) in exponential
+            .iter()
+            .zip([3.0, 1.0 + 2.0_f64.sqrt(), 2.0, 1.5])
+        {
+            assert!((p - q).abs() < 1e-9);
+        }
+        for curve in 1..=3 {
+            assert!(powers(curve).windows(2).all(|w| w[1] <= w[0]));
+        }
+        assert_eq!(
+            Config {
+                rst_bkt_decay: 4,
+                ..Config::default()
+            }
+            .check_restart_options(),
+            Err(ConfigError::OutOfRange("rst_bkt_decay"))
+        );
+    }
+
+    #[test]
+    fn test_level_blowup() {
+        let reasons = |force: bool| {
+            let config = Config {
+                rst_lvl_force: force,
+                ..Config::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+            let cold = Ema2::new(1).with_value(1.0);
+            for _ in 0..10_000 {
+                rst.update_level(10);
+            }
+            rst.update(4);
+            let before = rst.restart_reason(cold.as_view(), cold.as_view(), cold.as_view());
+            for l in 10..40 {
+                rst.update_level(l);
+            }
+            rst.update(4);
+            let after = rst.restart_reason(cold.as_view(), cold.as_view(), cold.as_view());
+            (before, after, rst.lvl.is_touched())
+        };
+        assert_eq!(
+            reasons(true),
+            (None, Some(RestartReason::LevelBlowup), true)
+        );
+        assert_eq!(reasons(false), (None, None, false));
+    }
+
+    #[test]
+    fn test_restart_mode_names() {
+        for mode in [
+            RestartMode::Dynamic,
+            RestartMode::Luby,
+            RestartMode::Never,
+            RestartMode::Geometric,
+            RestartMode::Hybrid,
+            RestartMode::Bucket,
+        ] {
+            assert_eq!(mode.to_string().parse::<RestartMode>(), Ok(mode));
+        }
+        assert_eq!("luby".parse::<RestartMode>(), Ok(RestartMode::Luby));
+        for name in ["", "Luby", "minisat", "dynamic "] {
+            assert!(name.parse::<RestartMode>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_min_restart_step() {
+        let configs = [
+            Config::default(),
+            Config {
+                rst_mode_switch: true,
+                ..Config::default()
+            },
+            Config {
+                rst_on_rescale: true,
+                ..Config::default()
+            },
+            Config {
+                rst_reuse_thr: 0.1,
+                ..Config::default()
+            },
+        ];
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        for config in configs {
+            let config = Config {
+                rst_step: 10,
+                ..config
+            };
+            let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+            rst.set_stage_parameters(1);
+            let mut restarts = Vec::new();
+            for i in 0..2_000 {
+                rst.update(4);
+                rst.add_learnt(ClauseId::from(1));
+                rst.update_reuse(Some(ClauseId::from(1)));
+                rst.handle(SolverEvent::ActivityRescale);
+                if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                    rst.handle(SolverEvent::Restart);
+                    restarts.push(i);
+                }
+            }
+            assert!(1 < restarts.len());
+            assert!(restarts.windows(2).all(|w| 10 <= w[1] - w[0]));
+        }
+    }
+
+    #[test]
+    fn test_conflicts_in_current_mode() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        for n in 1..=10 {
+            rst.update(4);
+            assert_eq!(rst.conflicts_in_current_mode(), n);
+        }
+        rst.handle(SolverEvent::Restart);
+        assert_eq!(rst.conflicts_in_current_mode(), 10);
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode(), RestartMode::Luby);
+        assert_eq!(rst.conflicts_in_current_mode(), 0);
+        for _ in 0..5 {
+            rst.update(4);
+        }
+        assert_eq!(rst.conflicts_in_current_mode(), 5);
+        rst.set_stage_parameters(2);
+        assert_eq!(rst.mode(), RestartMode::Dynamic);
+        assert_eq!(rst.conflicts_in_current_mode(), 0);
+    }
+
+    #[test]
+    fn test_restart_reason() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), cold_ema());
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        rst.update(4);
+        assert_eq!(
+            rst.restart_reason(asg.as_view(), lbd.as_view(), ent.as_view()),
+            Some(RestartReason::Energy)
+        );
+        rst.handle(SolverEvent::Restart);
+        rst.set_stage_parameters(1);
+        assert_eq!(rst.mode(), RestartMode::Luby);
+        let reasons = (0..rst.next_luby)
+            .map(|_| {
+                rst.update(4);
+                rst.restart_reason(asg.as_view(), lbd.as_view(), ent.as_view())
+            })
+            .collect::<Vec<_>>();
+        assert!(reasons[..reasons.len() - 1].iter().all(|r| r.is_none()));
+        assert_eq!(reasons.last(), Some(&Some(RestartReason::Luby)));
+    }
+
+    #[test]
+    fn test_describe_last_decision() {
+        let config = Config {
+            rst_on_rescale: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.describe_last_decision(), "no restart check yet");
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        rst.update(4);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        let description = rst.describe_last_decision();
+        assert!(description.starts_with("penetration energy -"));
+        assert!(description.ends_with("< threshold 0.0000 -> force"));
+        rst.handle(SolverEvent::Restart);
+        rst.mark_good_region(2);
+        rst.update(4);
+        assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        assert_eq!(
+            rst.describe_last_decision(),
+            "good region 1.0000 > threshold 0.0000 -> block"
+        );
+        rst.update(4);
+        rst.handle(SolverEvent::ActivityRescale);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        assert!(rst.describe_last_decision().starts_with("activity rescale"));
+    }
+
+    #[test]
+    fn test_age() {
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        for i in 1..=8 {
+            rst.set_segment_parameters(1 << i);
+        }
+        let (unit, scale) = (rst.penetration_energy_unit, rst.field_scale);
+        assert!(unit < FUEL);
+        assert!(1.0 / SCALE < scale);
+        rst.age(0.5);
+        assert!((rst.penetration_energy_unit - (unit + FUEL) / 2.0).abs() < 1e-12);
+        assert!((rst.field_scale - (scale + 1.0 / SCALE) / 2.0).abs() < 1e-12);
+        rst.age(1.0);
+        assert_eq!(rst.penetration_energy_unit, FUEL);
+        assert_eq!(rst.field_scale, 1.0 / SCALE);
+    }
+
+    #[test]
+    fn test_partial_restart() {
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let feed = |rst: &mut RestartManager| {
+            let learnt = [1, 2, 3]
+                .iter()
+                .map(|v| Lit::from(*v))
+                .collect::<Vec<Lit>>();
+            for _ in 0..20 {
+                rst.update(4);
+                rst.update_locality(&learnt);
+            }
+        };
+        let mut config = Config {
+            rst_locality_thr: 0.5,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        feed(&mut rst);
+        assert_eq!(
+            rst.restart_level(asg.as_view(), lbd.as_view(), ent.as_view(), 10),
+            None
+        );
+        config.rst_partial = true;
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        feed(&mut rst);
+        assert_eq!(
+            rst.restart_level(asg.as_view(), lbd.as_view(), ent.as_view(), 10),
+            Some(5)
+        );
+        // Without a blocking condition, it's a full restart.
+        let mut rst = RestartManager::instantiate(
+            &Config {
+                rst_partial: true,
+                ..Config::default()
+            },
+            &CNFDescription::default(),
+        );
+        rst.update(4);
+        assert_eq!(
+            rst.restart_level(asg.as_view(), lbd.as_view(), ent.as_view(), 10),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_restart_effectiveness() {
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        for _ in 0..200 {
+            rst.handle(SolverEvent::Restart);
+            for i in 0..100 {
+                rst.update(if i < EFFECT_WINDOW { 4 } else { 10 });
+            }
+        }
+        // Each segment averages (16 * 4 + 84 * 10) / 100 = 9.04.
+        assert!((rst.restart_effectiveness() - 5.04).abs() < 0.01);
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        for _ in 0..200 {
+            rst.handle(SolverEvent::Restart);
+            for _ in 0..100 {
+                rst.update(6);
+            }
+        }
+        assert!(rst.restart_effectiveness().abs() < 0.01);
+    }
+
+    #[test]
+    fn test_transition_restart() {
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), cold_ema(), cold_ema());
+        for flag in [false, true] {
+            let config = Config {
+                rst_mode_switch: true,
+                rst_stb_transition_restart: flag,
+                ..Config::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+            rst.update(4);
+            assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+            for stage in 1..=4 {
+                rst.set_stage_parameters(stage);
+                rst.update(4);
+                assert_eq!(
+                    rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()),
+                    flag
+                );
+                if flag {
+                    rst.handle(SolverEvent::Restart);
+                }
+                rst.update(4);
+                assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_stabilizer_scale() {
+        let config = Config {
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(rst.stabilizer_scale(), LUBY_STEP as f64);
+        for scale in [1.0, 0.5, -3.0, f64::NAN] {
+            assert!(rst.set_stabilizer_scale(scale).is_err());
+        }
+        rst.set_stage_parameters(1);
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), cold_ema(), cold_ema());
+        let mut restarts = vec![0];
+        for i in 1..=1_500 {
+            if i == 400 {
+                assert!(rst.set_stabilizer_scale(50.0).is_ok());
+            }
+            rst.update(4);
+            if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                rst.handle(SolverEvent::Restart);
+                restarts.push(i);
+            }
+        }
+        let intervals = restarts.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+        // Luby series 1, 2, 1, 1, 2, 4, 1, scaled by 100, and by 50 after the restart at 400
+        assert_eq!(intervals[..7], [100, 200, 100, 50, 100, 200, 50]);
+        assert_eq!(rst.stabilizer_scale(), 50.0);
+    }
+
+    #[test]
+    fn test_on_restart() {
+        let cold = Ema2::new(1).with_value(1.0);
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let log = calls.clone();
+        rst.on_restart(move |mode, n| log.lock().unwrap().push((mode, n)));
+        let mut expected = Vec::new();
+        let mut after = 0;
+        for i in 0..1_000 {
+            rst.update(4);
+            after += 1;
+            let lbd = if i % 10 == 9 { hot_ema() } else { cold.clone() };
+            if rst.restart(cold.as_view(), lbd.as_view(), cold.as_view()) {
+                rst.handle(SolverEvent::Restart);
+                expected.push((RestartMode::Dynamic, after));
+                after = 0;
+            }
+        }
+        assert!(!expected.is_empty());
+        // a clone shares the hook.
+        rst.clone().handle(SolverEvent::Restart);
+        expected.push((RestartMode::Dynamic, after));
+        assert_eq!(*calls.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_reset_phase() {
+        use crate::assign::{AssignStack, VarManipulateIF};
+        let cnf = CNFDescription {
+            num_of_variables: 4,
+            ..CNFDescription::default()
+        };
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), cold_ema());
+        for reset_phase in [false, true] {
+            let config = Config {
+                rst_reset_phase: reset_phase,
+                ..Config::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &cnf);
+            let mut assign = AssignStack::instantiate(&config, &cnf);
+            for vi in 1..=4 {
+                assign.var_mut(vi).turn_on(FlagVar::PHASE);
+            }
+            rst.update(4);
+            assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+            rst.handle(SolverEvent::Restart);
+            let event = rst.restart_event();
+            assert_eq!(event.is_some(), reset_phase);
+            if let Some(e) = event {
+                assign.handle(e);
+            }
+            for vi in 1..=4 {
+                assert_eq!(assign.var(vi).is(FlagVar::PHASE), !reset_phase);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_interval() {
+        let cold = Ema2::new(1).with_value(1.0);
+        let config = Config {
+            rst_min_interval: 500,
+            rst_epoch: 7,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        for _ in 0..3 {
+            let mut fired = None;
+            for i in 1..=1_000 {
+                rst.update(4);
+                // a hot LBD would force a restart at every check.
+                if rst.restart(cold.as_view(), hot_ema().as_view(), cold.as_view()) {
+                    fired = Some(i);
+                    break;
+                }
+                if i == 98 {
+                    // due at an epoch boundary, but under the floor
+                    assert!(rst.describe_last_decision().contains("min interval"));
+                }
+            }
+            rst.handle(SolverEvent::Restart);
+            // the first epoch boundary after the floor
+            let i = fired.expect("no restart");
+            assert!((500..500 + 7).contains(&i));
+        }
+    }
+
+    #[test]
+    fn test_zero_restart_step() {
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        let run = |step: usize| {
+            let config = Config {
+                rst_step: step,
+                rst_on_rescale: true,
+                ..Config::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+            let mut restarts = Vec::new();
+            for i in 0..100 {
+                rst.update(4);
+                rst.handle(SolverEvent::ActivityRescale);
+                if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                    rst.handle(SolverEvent::Restart);
+                    restarts.push(i);
+                }
+            }
+            restarts
+        };
+        assert_eq!(run(0), (0..100).collect::<Vec<usize>>());
+        assert_eq!(run(0), run(1));
+    }
+
+    #[test]
+    fn test_evaluator_name() {
+        assert_eq!(ProgressLocality::default().name(), "locality");
+    }
+
+    #[test]
+    fn test_hardness_scaling() {
+        let config = Config {
+            rst_hardness: true,
+            ..Config::default()
+        };
+        let asg = Ema2::new(1).with_value(1.0);
+        let ent = Ema2::new(1).with_slow(1000).with_value(1.0);
+        let mut lbd = Ema2::new(1).with_slow(1000).with_value(1.0);
+        lbd.update(1.1);
+        let first_restart = |num_of_clauses: usize| {
+            let cnf = CNFDescription {
+                num_of_variables: 100,
+                num_of_clauses,
+                ..CNFDescription::default()
+            };
+            let mut rst = RestartManager::instantiate(&config, &cnf);
+            (1..1_000)
+                .find(|_| {
+                    rst.update(4);
+                    rst.restart(asg.as_view(), lbd.as_view(), ent.as_view())
+                })
+                .unwrap()
+        };
+        assert!(first_restart(200) < first_restart(400));
+        assert!(first_restart(400) < first_restart(800));
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        let n = (1..1_000)
+            .find(|_| {
+                rst.update(4);
+                rst.restart(asg.as_view(), lbd.as_view(), ent.as_view())
+            })
+            .unwrap();
+        assert_eq!(n, first_restart(400));
+    }
+
+    #[test]
+    fn test_eagerness() {
+        let asg = Ema2::new(1).with_value(1.0);
+        let ent = Ema2::new(1).with_slow(1000).with_value(1.0);
+        let mut lbd = Ema2::new(1).with_slow(1000).with_value(1.0);
+        lbd.update(1.1);
+        let build = |eagerness: f64| {
+            let config = Config {
+                rst_eagerness: eagerness,
+                rst_reuse_thr: 0.5,
+                rst_locality_thr: 0.4,
+                ..Config::default()
+            };
+            RestartManager::with_config(&config, &CNFDescription::default()).unwrap()
+        };
+        let first_restart = |mut rst: RestartManager| {
+            (1..1_000)
+                .find(|_| {
+                    rst.update(4);
+                    rst.restart(asg.as_view(), lbd.as_view(), ent.as_view())
+                })
+                .unwrap()
+        };
+        let (shy, normal, eager) = (build(0.5), build(1.0), build(2.0));
+        assert!(normal.reuse_thr < shy.reuse_thr && eager.reuse_thr < normal.reuse_thr);
+        assert!(shy.locality_thr < normal.locality_thr && normal.locality_thr < eager.locality_thr);
+        assert!(first_restart(eager) < first_restart(normal.clone()));
+        assert!(first_restart(normal) < first_restart(shy));
+        for eagerness in [0.0, -1.0, f64::NAN] {
+            let config = Config {
+                rst_eagerness: eagerness,
+                ..Config::default()
+            };
+            assert!(RestartManager::with_config(&config, &CNFDescription::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_satisfiability_hint() {
+        let build = |hint: Option<bool>| {
+            let config = Config {
+                c_expect_sat: hint,
+                rst_reuse_thr: 0.5,
+                ..Config::default()
+            };
+            RestartManager::instantiate(&config, &CNFDescription::default())
+        };
+        let (sat, none, unsat) = (build(Some(true)), build(None), build(Some(false)));
+        assert!(unsat.penetration_energy_unit < none.penetration_energy_unit);
+        assert!(none.penetration_energy_unit < sat.penetration_energy_unit);
+        assert!(unsat.reuse_thr < none.reuse_thr && none.reuse_thr < sat.reuse_thr);
+        // a hint works as a factor of rst_eagerness.
+        let config = Config {
+            rst_eagerness: UNSAT_HINT_EAGERNESS,
+            ..Config::default()
+        };
+        let eager = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(unsat.penetration_energy_unit, eager.penetration_energy_unit);
+    }
+
+    #[test]
+    fn test_good_region() {
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        let (asg, lbd, ent) = (Ema2::new(1).with_value(1.0), hot_ema(), hot_ema());
+        rst.update(4);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        rst.handle(SolverEvent::Restart);
+        rst.mark_good_region(30);
+        for _ in 0..29 {
+            rst.update(4);
+            assert!(!rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+        }
+        rst.update(4);
+        assert!(rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()));
+    }
+
+    /// Drive every restart heuristic with a fixed pseudo-random script and
+    /// return the conflict indices at which a restart was granted.
+    fn restart_script() -> Vec<usize> {
+        let config = Config {
+            rst_db_size_thr: 1.5,
+            rst_jitter: 4,
+            rst_reuse_thr: 0.5,
+            rst_mode_switch: true,
+            rst_stb_budget: 50,
+            rst_locality_thr: 0.8,
+            ..Config::default()
+        };
+        let cnf = CNFDescription {
+            num_of_variables: 128,
+            ..CNFDescription::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &cnf);
+        let asg = Ema2::new(1).with_value(1.0);
+        let mut lbd = Ema2::new(4).with_slow(64).with_value(1.0);
+        let mut ent = Ema2::new(4).with_slow(64).with_value(1.0);
+        let mut rng: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut random = move || {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            rng
+        };
+        let mut decisions = Vec::new();
+        for step in 0..20_000 {
+            let r = random();
+            let l = 1 + (r >> 8) % 24;
+            rst.update(l as u16);
+            lbd.update(l as f64);
+            ent.update(((r >> 16) % 100) as f64 / 100.0);
+            let base = (r >> 24) % 64;
+            let vars = [base + 1, base + 2 + (r >> 32) % 4, base + 7];
+            rst.update_locality(
+                &vars
+                    .iter()
+                    .map(|v| Lit::from(*v as i32))
+                    .collect::<Vec<_>>(),
+            );
+            match r % 8 {
+                0..=2 => rst.add_learnt(ClauseId::from(1 + (r >> 40) as usize % 64)),
+                3 => rst.update_reuse(Some(ClauseId::from(1 + (r >> 40) as usize % 64))),
+                4 => rst.update_reuse(None),
+                5 => rst.set_db_size(((r >> 40) % 400) as usize),
+                6 if (r >> 40) % 64 == 0 => rst.set_stage_parameters(1 << ((r >> 48) % 4)),
+                _ => (),
+            }
+            if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+                rst.handle(SolverEvent::Restart);
+                decisions.push(step);
+            }
+        }
+        decisions
+    }
+
+    /// Any change in restart behavior shows up as a diff against
+    /// `tests/restart.golden`. After an intended change, regenerate it with
+    /// `SPLR_UPDATE_GOLDEN=1 cargo test test_restart_golden`.
+    #[cfg_attr(not(feature = "EMA_calibration"), test)]
+    #[allow(dead_code)]
+    fn test_restart_golden() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/restart.golden");
+        let decisions = restart_script()
+            .iter()
+            .map(|i| format!("{}\n", i))
+            .collect::<String>();
+        if std::env::var_os("SPLR_UPDATE_GOLDEN").is_some() {
+            std::fs::write(path, &decisions).expect("cannot write the golden file");
+            return;
+        }
+        let golden = std::fs::read_to_string(path).expect("cannot read the golden file");
+        assert!(
+            decisions == golden,
+            "restart decisions diverged from {}",
+            path
+        );
+        assert_eq!(restart_script(), restart_script());
     }
 }
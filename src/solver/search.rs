@@ -24,11 +24,23 @@ pub trait SolveIF {
 }
 
 macro_rules! RESTART {
-    ($asg: expr, $cdb: expr, $state: expr) => {
+    ($asg: expr, $cdb: expr, $state: expr) => {{
         $asg.cancel_until($asg.root_level());
-        $cdb.handle(SolverEvent::Restart);
-        $state.handle(SolverEvent::Restart);
-    };
+        // Past `rst_max_count`, just go back to the root level for root level
+        // jobs like clause DB reduction, without a restart.
+        if $state.restart.is_exhausted() {
+            None
+        } else {
+            $asg.handle(SolverEvent::Restart);
+            $cdb.handle(SolverEvent::Restart);
+            $state.handle(SolverEvent::Restart);
+            let event = $state.restart.restart_event();
+            if let Some(e) = event {
+                $asg.handle(e);
+            }
+            event
+        }
+    }};
 }
 
 impl SolveIF for Solver {
@@ -174,6 +186,12 @@ impl SolveIF for Solver {
         //
         state.progress(asg, cdb);
         let answer = search(asg, cdb, state);
+        if let Some(ref audit) = state.restart_audit {
+            let path = state.config.io_odir.join(&state.config.io_afile);
+            if audit.write(&state.config, &path).is_err() {
+                state.log(None, "failed to write the restart audit log");
+            }
+        }
         state.progress(asg, cdb);
         match answer {
             Ok(true) => {
@@ -239,6 +257,10 @@ fn search(
 ) -> Result<bool, SolverError> {
     let mut current_stage: Option<bool> = Some(true);
     let mut num_learnt = 0;
+    if state.config.show_journal {
+        let schedule = state.restart.schedule_description();
+        state.log(None, format!("restart schedule: {}", schedule));
+    }
 
     state.stm.initialize(
         (asg.derefer(assign::property::Tusize::NumUnassertedVar) as f64).sqrt() as usize,
@@ -255,9 +277,37 @@ fn search(
             asg.update_activity_tick();
             #[cfg(feature = "clause_rewarding")]
             cdb.update_activity_tick();
-            if 1 < handle_conflict(asg, cdb, state, &cc)? {
+            let rank = handle_conflict(asg, cdb, state, &cc)?;
+            state.restart.update(rank);
+            if 1 < rank {
                 num_learnt += 1;
             }
+            let num_conflict = asg.derefer(assign::property::Tusize::NumConflict);
+            if state.stats_channel.is_some()
+                && 0 < state.stats_interval
+                && num_conflict % state.stats_interval == 0
+            {
+                state.send_stats(asg, cdb);
+            }
+            if let Some(cap) = state.config.c_max_learnt {
+                let n = cdb.derefer(cdb::property::Tusize::NumLearnt);
+                if cap < n {
+                    // reduce at the root level, as done at the end of stages, but
+                    // without counting it as a restart.
+                    // `reduce` spares vivification targets; cut down to the half
+                    // of `cap` strictly so that the check can't fire at every conflict.
+                    asg.cancel_until(asg.root_level());
+                    cdb.reduce(asg, n - cap / 2);
+                    cdb.reduce_to(asg, cap / 2);
+                }
+            }
+            if let Some(line) = state.stats_line(asg, cdb, num_conflict) {
+                state.flush(format!("{}\n", line));
+            }
+            let controlled = state
+                .restart_controller
+                .as_ref()
+                .map(|c| c.ask(&state.stats(asg, cdb)));
             if state.stm.stage_ended(num_learnt) {
                 if let Some(p) = state.elapsed() {
                     if 1.0 <= p {
@@ -285,6 +335,7 @@ fn search(
                     }
                     if new_segment {
                         asg.rescale_activity((max_scale - scale) as f64 / max_scale as f64);
+                        state.handle(SolverEvent::ActivityRescale);
                         if !cfg!(feature = "no_clause_elimination") {
                             let mut elim = Eliminator::instantiate(&state.config, &state.cnf);
                             elim.simplify(asg, cdb, state, false)?;
@@ -298,15 +349,56 @@ fn search(
                     }
                 }
                 asg.clear_asserted_literals(cdb)?;
+                state.local_search.invalidate();
                 state.progress(asg, cdb);
                 asg.handle(SolverEvent::Stage(scale));
                 state.restart.set_stage_parameters(scale);
+                state.restart.set_stage_span(state.stm.current_span());
+                for name in state.restart.sanitize() {
+                    state.log(None, format!("reset a broken restart evaluator: {}", name));
+                }
                 current_stage = next_stage;
-            } else if state.restart.restart(
-                cdb.refer(cdb::property::TEma::LBD),
-                cdb.refer(cdb::property::TEma::Entanglement),
-            ) {
-                RESTART!(asg, cdb, state);
+            } else {
+                state
+                    .restart
+                    .set_db_size(cdb.derefer(cdb::property::Tusize::NumLearnt));
+                if let Some(restart) = controlled {
+                    if restart && !state.restart.is_exhausted() {
+                        RESTART!(asg, cdb, state);
+                        if state.local_search.is_enabled() {
+                            state.local_search.improve_phases(asg, cdb);
+                        }
+                    }
+                } else {
+                    if let Some(thr) = asg.assign_progress().threshold() {
+                        state.restart.set_near_sol_thr(thr);
+                    }
+                    state.restart.set_lbd_trend(cdb.lbd_progress().trend());
+                    let level = state.restart.restart_level(
+                        asg.refer(assign::property::TEma::AssignRate),
+                        cdb.refer(cdb::property::TEma::LBD),
+                        cdb.refer(cdb::property::TEma::Entanglement),
+                        asg.decision_level(),
+                    );
+                    if let Some(ref mut audit) = state.restart_audit {
+                        audit.push(
+                            num_conflict,
+                            cdb.refer(cdb::property::TEma::LBD),
+                            cdb.refer(cdb::property::TEma::Entanglement),
+                            state.restart.describe_last_decision(),
+                        );
+                    }
+                    match level {
+                        Some(0) => {
+                            RESTART!(asg, cdb, state);
+                            if state.local_search.is_enabled() {
+                                state.local_search.improve_phases(asg, cdb);
+                            }
+                        }
+                        Some(lv) if asg.root_level() < lv => asg.cancel_until(lv),
+                        _ => (),
+                    }
+                }
             }
             if let Some(na) = asg.best_assigned() {
                 state.flush("");
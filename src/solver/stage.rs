@@ -2,7 +2,7 @@
 /// I define it as a 'search mode', or stage, changer.
 /// A stage is a span sharing same restart parameters.
 /// And it also define the interval of clause reduction.
-use crate::types::*;
+use {crate::types::*, std::fmt};
 
 #[derive(Clone, Debug, Default)]
 pub struct StageManager {
@@ -14,22 +14,49 @@ pub struct StageManager {
     scale: usize,
     end_of_stage: usize,
     next_is_new_segment: bool,
+    /// keep the span of stages at `unit_size`
+    fixed_span: bool,
+    /// the max span of stages (0: unlimited)
+    max_span: usize,
 }
 
 impl Instantiate for StageManager {
-    fn instantiate(_: &Config, cnf: &CNFDescription) -> StageManager {
+    fn instantiate(config: &Config, cnf: &CNFDescription) -> StageManager {
         let unit_size = (cnf.num_of_variables as f64).sqrt() as usize;
         StageManager {
             unit_size,
             scale: 1,
             end_of_stage: unit_size,
             next_is_new_segment: true,
+            fixed_span: config.rst_fixed_stage,
+            max_span: config.rst_stb_max,
             ..StageManager::default()
         }
     }
     fn handle(&mut self, _: SolverEvent) {}
 }
 
+impl fmt::Display for StageManager {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Stage[stage:{}, cycle:{}, span:{}, max span:{}]",
+            self.stage,
+            self.cycle,
+            self.current_span(),
+            self.max_span,
+        )
+    }
+}
+
+impl EmaIF for StageManager {
+    /// return the number of learnts at which the current stage ends,
+    /// i.e. the next toggle of stabilization.
+    fn get_fast(&self) -> f64 {
+        self.end_of_stage as f64
+    }
+}
+
 impl StageManager {
     pub fn new(unit_size: usize) -> Self {
         StageManager {
@@ -41,6 +68,8 @@ impl StageManager {
             scale: 1,
             end_of_stage: unit_size,
             next_is_new_segment: true,
+            fixed_span: false,
+            max_span: 0,
         }
     }
     pub fn initialize(&mut self, unit_size: usize) {
@@ -82,9 +111,18 @@ impl StageManager {
     pub fn stage_ended(&self, now: usize) -> bool {
         self.end_of_stage < now
     }
-    /// returns the number of conflicts in the current stage
+    /// returns the number of conflicts in the current stage, saturated at `max_span`
     pub fn current_span(&self) -> usize {
-        self.cycle * self.unit_size
+        let span = if self.fixed_span {
+            self.unit_size
+        } else {
+            self.cycle.saturating_mul(self.unit_size)
+        };
+        if 0 < self.max_span {
+            span.min(self.max_span)
+        } else {
+            span
+        }
     }
     pub fn current_stage(&self) -> usize {
         self.stage
@@ -116,3 +154,65 @@ impl StageManager {
         2 * self.luby_iter.max_value()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_stage_span() {
+        let spans = |fixed: bool| {
+            let config = Config {
+                rst_fixed_stage: fixed,
+                ..Config::default()
+            };
+            let mut stm = StageManager::instantiate(&config, &CNFDescription::default());
+            let mut now = 0;
+            (0..16)
+                .map(|_| {
+                    stm.prepare_new_stage(10, now);
+                    let span = stm.end_of_stage - now;
+                    now = stm.end_of_stage;
+                    span
+                })
+                .collect::<Vec<usize>>()
+        };
+        assert!(spans(true).iter().all(|s| *s == 10));
+        let growing = spans(false);
+        assert!(growing.windows(2).all(|w| w[0] <= w[1]));
+        assert!(growing[0] < growing[15]);
+    }
+
+    #[test]
+    fn test_max_span() {
+        let config = Config {
+            rst_stb_max: 50,
+            ..Config::default()
+        };
+        let mut stm = StageManager::instantiate(&config, &CNFDescription::default());
+        let mut now = 0;
+        let spans = (0..200)
+            .map(|_| {
+                stm.prepare_new_stage(10, now);
+                now = stm.end_of_stage;
+                stm.current_span()
+            })
+            .collect::<Vec<usize>>();
+        assert!(spans.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(spans.last(), Some(&50));
+        assert!(stm.to_string().ends_with("max span:50]"));
+    }
+
+    #[test]
+    fn test_next_trigger() {
+        let cnf = CNFDescription {
+            num_of_variables: 100,
+            ..CNFDescription::default()
+        };
+        let mut stm = StageManager::instantiate(&Config::default(), &cnf);
+        assert_eq!(stm.get(), 10.0);
+        assert_eq!(stm.get(), stm.trend());
+        stm.prepare_new_stage(10, 25);
+        assert_eq!(stm.get(), (25 + stm.current_span()) as f64);
+    }
+}
@@ -0,0 +1,404 @@
+//! Module `trace` records inputs to restart heuristics and replays them,
+//! for regression testing across versions. It also keeps an audit log of
+//! restart decisions in a run, to bundle them with reproducible results.
+//!
+//! A trace is stored as a JSON array of records like
+//! `{"op":"update","value":4}`. Since the records are flat, a small
+//! hand-written encoder and decoder are used instead of an extra dependency.
+use {
+    super::{RestartIF, SolverEvent},
+    crate::types::*,
+    std::{
+        fmt::Write,
+        fs::File,
+        io::{BufWriter, Write as _},
+        iter::Peekable,
+        path::Path,
+        str::Chars,
+    },
+};
+
+/// An input to [`RestartIF`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum RestartInput {
+    /// `update` with the LBD of a new learnt.
+    Update(u16),
+    /// `set_db_size` with the number of learnts.
+    DbSize(usize),
+    /// `add_learnt` with the id of a new learnt.
+    Learnt(ClauseId),
+    /// `update_reuse` with the conflicting clause.
+    Reuse(Option<ClauseId>),
+    /// `update_locality` with the literals of a new learnt.
+    Locality(Vec<i32>),
+    /// `set_stage_parameters` with the stage scale.
+    Stage(usize),
+    /// `restart` with the (fast, slow) values of the assign, LBD and
+    /// entanglement EMAs, and the decision it made.
+    Check([f64; 6], bool),
+}
+
+/// A sequence of [`RestartInput`]s with the recorded restart decisions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RestartTrace {
+    pub records: Vec<RestartInput>,
+}
+
+impl RestartTrace {
+    /// apply `input` to `rst` and append it to the trace.
+    /// Return the restart decision if `input` is a `Check`, whose given
+    /// decision is replaced with the actual one.
+    pub fn record(&mut self, rst: &mut impl RestartIF, mut input: RestartInput) -> Option<bool> {
+        let decision = apply(rst, &input);
+        if let (RestartInput::Check(_, d), Some(actual)) = (&mut input, decision) {
+            *d = actual;
+        }
+        self.records.push(input);
+        decision
+    }
+    /// feed the trace to `rst`, and return the index of the first record whose
+    /// decision differs from the recorded one, or `None` if all decisions match.
+    pub fn replay(&self, rst: &mut impl RestartIF) -> Option<usize> {
+        self.records.iter().position(|input| match input {
+            RestartInput::Check(_, expected) => apply(rst, input) != Some(*expected),
+            _ => {
+                apply(rst, input);
+                false
+            }
+        })
+    }
+    /// return the trace as a JSON array.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[\n");
+        for (i, input) in self.records.iter().enumerate() {
+            let _ = match input {
+                RestartInput::Update(lbd) => write!(json, r#"{{"op":"update","value":{}}}"#, lbd),
+                RestartInput::DbSize(n) => write!(json, r#"{{"op":"db_size","value":{}}}"#, n),
+                RestartInput::Learnt(cid) => {
+                    write!(json, r#"{{"op":"learnt","value":{}}}"#, usize::from(*cid))
+                }
+                RestartInput::Reuse(Some(cid)) => {
+                    write!(json, r#"{{"op":"reuse","value":{}}}"#, usize::from(*cid))
+                }
+                RestartInput::Reuse(None) => write!(json, r#"{{"op":"reuse","value":null}}"#),
+                RestartInput::Locality(lits) => {
+                    write!(json, r#"{{"op":"locality","value":{:?}}}"#, lits)
+                }
+                RestartInput::Stage(scale) => write!(json, r#"{{"op":"stage","value":{}}}"#, scale),
+                RestartInput::Check(emas, decision) => write!(
+                    json,
+                    r#"{{"op":"restart","value":{:?},"decision":{}}}"#,
+                    emas, decision
+                ),
+            };
+            json.push_str(if i + 1 < self.records.len() {
+                ",\n"
+            } else {
+                "\n"
+            });
+        }
+        json.push_str("]\n");
+        json
+    }
+    /// build a trace from a JSON array made by `to_json`.
+    pub fn from_json(json: &str) -> Result<RestartTrace, SolverError> {
+        let Json::Array(items) = Json::from_str(json)? else {
+            return Err(SolverError::IOError);
+        };
+        let records = items
+            .iter()
+            .map(decode)
+            .collect::<Option<Vec<RestartInput>>>()
+            .ok_or(SolverError::IOError)?;
+        Ok(RestartTrace { records })
+    }
+}
+
+/// A log of every restart decision in a run with its inputs. Written along
+/// with the configuration and the seed, another run can verify identical behavior.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RestartAudit {
+    pub entries: Vec<String>,
+}
+
+impl RestartAudit {
+    /// append a restart decision made at `num_conflict` from the LBD and
+    /// entanglement EMAs, described by [`RestartIF::describe_last_decision`].
+    pub fn push(&mut self, num_conflict: usize, lbd: &EmaView, ent: &EmaView, decision: String) {
+        self.entries.push(format!(
+            "restart {} lbd {:.4}/{:.4} ent {:.4}/{:.4}: {}",
+            num_conflict,
+            lbd.get_fast(),
+            lbd.get_slow(),
+            ent.get_fast(),
+            ent.get_slow(),
+            decision,
+        ));
+    }
+    /// write the configuration, the seed and the decisions to `path`.
+    pub fn write(&self, config: &Config, path: &Path) -> Result<(), SolverError> {
+        let mut buf = File::create(path)
+            .map(BufWriter::new)
+            .map_err(|_| SolverError::IOError)?;
+        writeln!(buf, "splr {}", env!("CARGO_PKG_VERSION"))
+            .and_then(|_| writeln!(buf, "seed {}", config.c_seed))
+            .and_then(|_| writeln!(buf, "config {:?}", config))
+            .and_then(|_| self.entries.iter().try_for_each(|e| writeln!(buf, "{}", e)))
+            .and_then(|_| buf.flush())
+            .map_err(|_| SolverError::IOError)
+    }
+}
+
+/// apply `input` to `rst`; return the restart decision if `input` is a `Check`.
+fn apply(rst: &mut impl RestartIF, input: &RestartInput) -> Option<bool> {
+    match input {
+        RestartInput::Update(lbd) => rst.update(*lbd),
+        RestartInput::DbSize(n) => rst.set_db_size(*n),
+        RestartInput::Learnt(cid) => rst.add_learnt(*cid),
+        RestartInput::Reuse(cid) => rst.update_reuse(*cid),
+        RestartInput::Locality(lits) => {
+            rst.update_locality(&lits.iter().map(|l| Lit::from(*l)).collect::<Vec<Lit>>())
+        }
+        RestartInput::Stage(scale) => rst.set_stage_parameters(*scale),
+        RestartInput::Check(e, _) => {
+            let asg = Ema2::new(1).with_values(e[0], e[1]);
+            let lbd = Ema2::new(1).with_values(e[2], e[3]);
+            let ent = Ema2::new(1).with_values(e[4], e[5]);
+            let decision = rst.restart(asg.as_view(), lbd.as_view(), ent.as_view());
+            if decision {
+                rst.handle(SolverEvent::Restart);
+            }
+            return Some(decision);
+        }
+    }
+    None
+}
+
+/// A JSON value, restricted to what traces and [`RestartStats`](super::RestartStats) need.
+#[derive(Debug, PartialEq)]
+pub(super) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// parse a string holding exactly one JSON value.
+    pub(super) fn from_str(json: &str) -> Result<Json, SolverError> {
+        let mut chars = json.chars().peekable();
+        let value = parse(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next().is_some() {
+            return Err(SolverError::IOError);
+        }
+        Ok(value)
+    }
+    pub(super) fn get(&self, key: &str) -> Option<&Json> {
+        let Json::Object(pairs) = self else {
+            return None;
+        };
+        pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+    /// return a non-negative integer value.
+    pub(super) fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(x) if 0.0 <= *x && x.fract() == 0.0 => Some(*x as usize),
+            _ => None,
+        }
+    }
+    /// return a valid clause id, which is never zero.
+    fn as_cid(&self) -> Option<ClauseId> {
+        self.as_usize()
+            .filter(|n| 0 < *n && *n <= u32::MAX as usize)
+            .map(ClauseId::from)
+    }
+}
+
+fn decode(item: &Json) -> Option<RestartInput> {
+    let Json::String(op) = item.get("op")? else {
+        return None;
+    };
+    let value = item.get("value")?;
+    match op.as_str() {
+        "update" => value
+            .as_usize()
+            .filter(|n| *n <= u16::MAX as usize)
+            .map(|n| RestartInput::Update(n as u16)),
+        "db_size" => value.as_usize().map(RestartInput::DbSize),
+        "learnt" => value.as_cid().map(RestartInput::Learnt),
+        "reuse" if *value == Json::Null => Some(RestartInput::Reuse(None)),
+        "reuse" => value.as_cid().map(|cid| RestartInput::Reuse(Some(cid))),
+        "locality" => {
+            let Json::Array(lits) = value else {
+                return None;
+            };
+            lits.iter()
+                .map(|l| match l {
+                    Json::Number(x)
+                        if x.fract() == 0.0 && *x != 0.0 && x.abs() <= i32::MAX as f64 =>
+                    {
+                        Some(*x as i32)
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<i32>>>()
+                .map(RestartInput::Locality)
+        }
+        "stage" => value.as_usize().map(RestartInput::Stage),
+        "restart" => {
+            let (Json::Array(vals), Some(Json::Bool(decision))) = (value, item.get("decision"))
+            else {
+                return None;
+            };
+            let mut emas = [0.0; 6];
+            if vals.len() != emas.len() {
+                return None;
+            }
+            for (e, v) in emas.iter_mut().zip(vals.iter()) {
+                let Json::Number(x) = v else {
+                    return None;
+                };
+                *e = *x;
+            }
+            Some(RestartInput::Check(emas, *decision))
+        }
+        _ => None,
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// parse a JSON value. Escape sequences in strings are not supported.
+fn parse(chars: &mut Peekable<Chars>) -> Result<Json, SolverError> {
+    skip_whitespace(chars);
+    match chars.peek() {
+        Some('[') => {
+            chars.next();
+            let mut items = Vec::new();
+            skip_whitespace(chars);
+            if chars.peek() == Some(&']') {
+                chars.next();
+                return Ok(Json::Array(items));
+            }
+            loop {
+                items.push(parse(chars)?);
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => (),
+                    Some(']') => return Ok(Json::Array(items)),
+                    _ => return Err(SolverError::IOError),
+                }
+            }
+        }
+        Some('{') => {
+            chars.next();
+            let mut pairs = Vec::new();
+            skip_whitespace(chars);
+            if chars.peek() == Some(&'}') {
+                chars.next();
+                return Ok(Json::Object(pairs));
+            }
+            loop {
+                let Json::String(key) = parse(chars)? else {
+                    return Err(SolverError::IOError);
+                };
+                skip_whitespace(chars);
+                if chars.next() != Some(':') {
+                    return Err(SolverError::IOError);
+                }
+                pairs.push((key, parse(chars)?));
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => (),
+                    Some('}') => return Ok(Json::Object(pairs)),
+                    _ => return Err(SolverError::IOError),
+                }
+            }
+        }
+        Some('"') => {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => return Ok(Json::String(s)),
+                    Some('\\') | None => return Err(SolverError::IOError),
+                    Some(c) => s.push(c),
+                }
+            }
+        }
+        Some(_) => {
+            let mut token = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || "+-.".contains(*c)) {
+                token.push(c);
+            }
+            match token.as_str() {
+                "null" => Ok(Json::Null),
+                "true" => Ok(Json::Bool(true)),
+                "false" => Ok(Json::Bool(false)),
+                _ => token
+                    .parse::<f64>()
+                    .map(Json::Number)
+                    .map_err(|_| SolverError::IOError),
+            }
+        }
+        None => Err(SolverError::IOError),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::solver::RestartManager};
+
+    #[test]
+    fn test_trace_replay() {
+        let config = Config {
+            rst_reuse_thr: 0.5,
+            rst_mode_switch: true,
+            ..Config::default()
+        };
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        let mut trace = RestartTrace::default();
+        let mut decisions = Vec::new();
+        for i in 0..2_000_usize {
+            let lbd = 1 + (i * 7 + i / 13) % 17;
+            trace.record(&mut rst, RestartInput::Update(lbd as u16));
+            trace.record(&mut rst, RestartInput::Learnt(ClauseId::from(1 + i % 40)));
+            trace.record(
+                &mut rst,
+                RestartInput::Reuse(Some(ClauseId::from(1 + i % 37))),
+            );
+            trace.record(&mut rst, RestartInput::Reuse(None));
+            trace.record(&mut rst, RestartInput::Locality(vec![1, -2, i as i32 + 3]));
+            trace.record(&mut rst, RestartInput::DbSize(i / 3));
+            if i % 500 == 0 {
+                trace.record(&mut rst, RestartInput::Stage(1 + i / 500));
+            }
+            let x = (i % 11) as f64 / 10.0;
+            let check = RestartInput::Check([1.0, 1.0, 0.5 + x, 1.0, 1.0, 0.9 + 0.1 * x], false);
+            decisions.push(trace.record(&mut rst, check).unwrap());
+        }
+        assert!(decisions.iter().any(|d| *d));
+        assert!(decisions.iter().any(|d| !*d));
+        let json = trace.to_json();
+        let loaded = RestartTrace::from_json(&json).unwrap();
+        assert_eq!(loaded, trace);
+        let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+        assert_eq!(loaded.replay(&mut rst), None);
+        // another configuration should take different decisions.
+        let mut rst = RestartManager::instantiate(&Config::default(), &CNFDescription::default());
+        assert!(loaded.replay(&mut rst).is_some());
+        assert!(RestartTrace::from_json(r#"[{"op":"learnt","value":0}]"#).is_err());
+        assert!(RestartTrace::from_json("[").is_err());
+        assert_eq!(
+            RestartTrace::from_json(" [ ] "),
+            Ok(RestartTrace::default())
+        );
+    }
+}
@@ -2,13 +2,14 @@
 use {
     crate::{
         assign, cdb,
-        solver::{RestartManager, SolverEvent, StageManager},
+        solver::{LocalSearch, RestartAudit, RestartIF, RestartManager, SolverEvent, StageManager},
         types::*,
     },
     std::{
         fmt,
         io::{stdout, Write},
         ops::{Index, IndexMut},
+        sync::{mpsc::Sender, Arc, Mutex},
         time::{Duration, Instant},
     },
 };
@@ -128,6 +129,16 @@ pub struct State {
     pub time_limit: f64,
     /// logging facility.
     log_messages: Vec<String>,
+    /// an optional receiver of periodic statistics snapshots
+    pub stats_channel: Option<Sender<SolverStats>>,
+    /// the number of conflicts between two snapshots (0: no snapshot)
+    pub stats_interval: usize,
+    /// an optional function which decides restarts instead of `restart`
+    pub restart_controller: Option<RestartController>,
+    /// the log of restart decisions, kept if `config.io_afile` is given
+    pub restart_audit: Option<RestartAudit>,
+    /// local search to improve phases at restarts
+    pub local_search: LocalSearch,
 }
 
 impl Default for State {
@@ -155,6 +166,11 @@ impl Default for State {
             start: Instant::now(),
             time_limit: 0.0,
             log_messages: Vec::new(),
+            stats_channel: None,
+            stats_interval: 1_000,
+            restart_controller: None,
+            restart_audit: None,
+            local_search: LocalSearch::default(),
         }
     }
 }
@@ -191,25 +207,33 @@ impl Instantiate for State {
             cnf: cnf.clone(),
             restart: RestartManager::instantiate(config, cnf),
             stm: StageManager::instantiate(config, cnf),
+            local_search: LocalSearch::instantiate(config, cnf),
             target: cnf.clone(),
             time_limit: config.c_timeout,
+            restart_audit: (!config.io_afile.as_os_str().is_empty()).then(RestartAudit::default),
             ..State::default()
         }
     }
     fn handle(&mut self, e: SolverEvent) {
         match e {
             SolverEvent::NewVar => {
+                self.cnf.num_of_variables += 1;
                 self.target.num_of_variables += 1;
+                self.restart.handle(SolverEvent::NewVar);
+            }
+            SolverEvent::ActivityRescale => {
+                self.restart.handle(SolverEvent::ActivityRescale);
             }
             SolverEvent::Assert(_) => (),
             SolverEvent::Conflict => (),
             SolverEvent::Eliminate(_) => (),
             SolverEvent::Instantiate => (),
-            SolverEvent::Reinitialize => (),
+            SolverEvent::Reinitialize => self.restart.reset(),
             SolverEvent::Restart => {
                 self[Stat::Restart] += 1;
                 self.restart.handle(SolverEvent::Restart);
             }
+            SolverEvent::ResetPhase => (),
             SolverEvent::Stage(_) => (),
 
             #[cfg(feature = "clause_vivification")]
@@ -609,6 +633,124 @@ impl State {
     }
 }
 
+impl State {
+    /// return a snapshot of the current statistics.
+    pub fn stats<A, C>(&self, asg: &A, cdb: &C) -> SolverStats
+    where
+        A: PropertyDereference<assign::property::Tusize, usize>
+            + PropertyReference<assign::property::TEma, EmaView>,
+        C: PropertyDereference<cdb::property::Tusize, usize>
+            + PropertyReference<cdb::property::TEma, EmaView>,
+    {
+        let lbd: &EmaView = cdb.refer(cdb::property::TEma::LBD);
+        SolverStats {
+            num_conflict: asg.derefer(assign::property::Tusize::NumConflict),
+            num_decision: asg.derefer(assign::property::Tusize::NumDecision),
+            num_propagation: asg.derefer(assign::property::Tusize::NumPropagation),
+            num_restart: self[Stat::Restart],
+            num_learnt: cdb.derefer(cdb::property::Tusize::NumLearnt),
+            num_unasserted_var: asg.derefer(assign::property::Tusize::NumUnassertedVar),
+            lbd: lbd.get_fast(),
+            lbd_trend: lbd.trend(),
+            propagation_per_conflict: asg
+                .refer(assign::property::TEma::PropagationPerConflict)
+                .get_fast(),
+            elapsed: self.start.elapsed().as_secs_f64(),
+        }
+    }
+    /// send a snapshot of the current statistics to `stats_channel`.
+    /// The channel is closed if the receiver has hung up.
+    pub fn send_stats<A, C>(&mut self, asg: &A, cdb: &C)
+    where
+        A: PropertyDereference<assign::property::Tusize, usize>
+            + PropertyReference<assign::property::TEma, EmaView>,
+        C: PropertyDereference<cdb::property::Tusize, usize>
+            + PropertyReference<cdb::property::TEma, EmaView>,
+    {
+        let Some(tx) = &self.stats_channel else {
+            return;
+        };
+        if tx.send(self.stats(asg, cdb)).is_err() {
+            self.stats_channel = None;
+        }
+    }
+    /// return the statistics comment line due at `num_conflict` by `io_stat_int`.
+    /// It's suppressed by `quiet_mode` or feature `no_IO`.
+    pub fn stats_line<A, C>(&self, asg: &A, cdb: &C, num_conflict: usize) -> Option<String>
+    where
+        A: PropertyDereference<assign::property::Tusize, usize>
+            + PropertyReference<assign::property::TEma, EmaView>,
+        C: PropertyDereference<cdb::property::Tusize, usize>
+            + PropertyReference<cdb::property::TEma, EmaView>,
+    {
+        let interval = self.config.io_stat_int;
+        if cfg!(feature = "no_IO")
+            || self.config.quiet_mode
+            || interval == 0
+            || !num_conflict.is_multiple_of(interval)
+        {
+            return None;
+        }
+        Some(format!("{}", self.stats(asg, cdb)))
+    }
+}
+
+/// A snapshot of statistics sent by [`State::send_stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SolverStats {
+    pub num_conflict: usize,
+    pub num_decision: usize,
+    pub num_propagation: usize,
+    pub num_restart: usize,
+    pub num_learnt: usize,
+    pub num_unasserted_var: usize,
+    /// the fast EMA of learnt clauses' LBD
+    pub lbd: f64,
+    /// the trend of learnt clauses' LBD
+    pub lbd_trend: f64,
+    /// an EMA of the number of propagations between conflicts
+    pub propagation_per_conflict: f64,
+    /// elapsed time in seconds
+    pub elapsed: f64,
+}
+
+/// A user-provided function called at each conflict; the solver restarts
+/// iff it returns `true`. See [`Solver::with_restart_controller`](`crate::solver::Solver::with_restart_controller`).
+#[allow(clippy::type_complexity)]
+#[derive(Clone)]
+pub struct RestartController(pub Arc<Mutex<dyn FnMut(&SolverStats) -> bool + Send>>);
+
+impl fmt::Debug for RestartController {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RestartController")
+    }
+}
+
+impl RestartController {
+    /// ask the controller whether to restart now.
+    pub fn ask(&self, stats: &SolverStats) -> bool {
+        self.0.lock().is_ok_and(|mut f| f(stats))
+    }
+}
+
+/// A comment line in the conventional format of SAT competitions.
+impl fmt::Display for SolverStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "c conflicts: {} decisions: {} propagations: {} restarts: {} learnts: {} lbd: {:.2} ppc: {:.2} time: {:.2}",
+            self.num_conflict,
+            self.num_decision,
+            self.num_propagation,
+            self.num_restart,
+            self.num_learnt,
+            self.lbd,
+            self.propagation_per_conflict,
+            self.elapsed,
+        )
+    }
+}
+
 impl fmt::Display for State {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let tm: f64 = (self.start.elapsed().as_millis() as f64) / 1_000.0;
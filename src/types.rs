@@ -4,7 +4,7 @@ pub use crate::{
     assign::AssignReason,
     cdb::{Clause, ClauseDB, ClauseIF, ClauseId, ClauseIdIF},
     config::Config,
-    primitive::{ema::*, luby::*},
+    primitive::{ema::*, geometric::*, luby::*},
     solver::SolverEvent,
 };
 
@@ -379,6 +379,8 @@ pub enum SolverError {
     OutOfRange,
     RootLevelConflict(ConflictContext),
     EmptyClause,
+    /// a malformed line in a CNF stream, with its line number from 1
+    ParseError(usize),
     TimeOut,
     SolverBug,
     UndescribedError,
@@ -0,0 +1,85 @@
+/// Checks that the per-conflict path of restart heuristics doesn't allocate,
+/// and measures its cost.
+///```ignore
+/// cargo test --test restart-alloc --release -- --nocapture
+///```
+use {
+    splr::{
+        solver::{RestartIF, RestartManager, SolverEvent},
+        types::*,
+    },
+    std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Instant,
+    },
+};
+
+struct CountingAllocator;
+
+static NUM_ALLOC: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// count allocations only by the measuring thread, not by the test harness.
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.try_with(|c| c.get()).unwrap_or(false) {
+            NUM_ALLOC.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn restart_update_is_allocation_free() {
+    const NUM_CONFLICT: usize = 1_000_000;
+    let config = Config {
+        rst_locality_thr: 0.9,
+        rst_reuse_thr: 0.8,
+        ..Config::default()
+    };
+    let mut rst = RestartManager::instantiate(&config, &CNFDescription::default());
+    let asg = Ema2::new(1).with_value(1.0);
+    let lbd = Ema2::new(16).with_slow(8192).with_values(4.4, 4.0);
+    let ent = Ema2::new(16).with_slow(8192).with_value(4.0);
+    let learnts = (1..=64)
+        .map(|i| (0..8).map(|j| Lit::from(i + j)).collect::<Vec<Lit>>())
+        .collect::<Vec<_>>();
+    // warm up, to let the reused buffers grow
+    rst.update_locality(&learnts[0]);
+    rst.update_locality(&learnts[1]);
+    COUNTING.with(|c| c.set(true));
+    let start = Instant::now();
+    let mut num_restart = 0;
+    for i in 0..NUM_CONFLICT {
+        rst.update(1 + (i % 12) as u16);
+        rst.update_reuse(Some(ClauseId::from(1 + i % 16)));
+        rst.add_learnt(ClauseId::from(1 + i % 32));
+        rst.update_locality(&learnts[i % learnts.len()]);
+        rst.set_db_size(i / 2);
+        if rst.restart(asg.as_view(), lbd.as_view(), ent.as_view()) {
+            rst.handle(SolverEvent::Restart);
+            num_restart += 1;
+        }
+    }
+    let elapsed = start.elapsed();
+    COUNTING.with(|c| c.set(false));
+    let allocated = NUM_ALLOC.load(Ordering::Relaxed);
+    println!(
+        "{} conflicts, {} restarts: {:.1} ns/conflict",
+        NUM_CONFLICT,
+        num_restart,
+        elapsed.as_nanos() as f64 / NUM_CONFLICT as f64,
+    );
+    assert_eq!(allocated, 0);
+}